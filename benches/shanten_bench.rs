@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mjtools::shanten::*;
+use mjtools::types::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// 問題ファイルから14枚の手牌を読み込む
+fn load_problems(filename: &str) -> Vec<Vec<TileId>> {
+    let file = match File::open(filename) {
+        Err(e) => panic!("couldn't open problem file: {}", e),
+        Ok(f) => f,
+    };
+    BufReader::new(file)
+        .lines()
+        .map(|line| {
+            line.unwrap()
+                .split(' ')
+                .take(14)
+                .map(|s| TileId::ALL[s.parse::<usize>().unwrap()])
+                .collect()
+        })
+        .collect()
+}
+
+fn bench_shanten(c: &mut Criterion) {
+    for name in ["hon", "koku", "normal", "tin"] {
+        let problems = load_problems(&format!("./data/p_{}_10000.txt", name));
+        c.bench_function(&format!("shanten/p_{}_10000", name), |b| {
+            b.iter(|| {
+                for tiles in &problems {
+                    black_box(calculate_shanten(black_box(tiles)));
+                }
+            })
+        });
+    }
+}
+
+fn bench_effective_tiles(c: &mut Criterion) {
+    // 自摸前の13枚で有効牌を列挙
+    let problems: Vec<Vec<TileId>> = load_problems("./data/p_normal_10000.txt")
+        .into_iter()
+        .take(1000)
+        .map(|tiles| tiles[..13].to_vec())
+        .collect();
+    c.bench_function("effective_tiles/p_normal_1000", |b| {
+        b.iter(|| {
+            for tiles in &problems {
+                black_box(listup_effective_tiles(black_box(tiles)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_shanten, bench_effective_tiles
+}
+criterion_main!(benches);
@@ -3,9 +3,13 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::Path;
 
+// 数牌1種類あたりのテーブルサイズ（9種の枚数0-4を5進数で符号化）
+const SUHAI_TABLE_SIZE: usize = 5usize.pow(9);
+
 fn main() {
-    // ビルドスクリプトに変更があれば実行
+    // ビルドスクリプトか向聴数データに変更があれば実行
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=data/shanten.dat");
 
     // 向聴数データを開く
     let infile = match File::open("./data/shanten.dat") {
@@ -13,32 +17,59 @@ fn main() {
         Ok(f) => f,
     };
 
-    let mut builder = phf_codegen::Map::new();
+    // 面子数と塔子数を1バイトに詰める（上位4bit: 面子数, 下位4bit: 塔子数）
+    let mut table = vec![0u8; SUHAI_TABLE_SIZE];
     for line in BufReader::new(infile).lines() {
         let line_str = line.unwrap();
         let entry: Vec<&str> = line_str.split(' ').collect();
         // 2通りの面子・塔子が入っているので向聴数が少ない方を選択
-        let num_mentsu_a = entry[1].parse::<i32>().unwrap();
-        let num_tatsu_a = entry[2].parse::<i32>().unwrap();
-        let num_mentsu_b = entry[3].parse::<i32>().unwrap();
-        let num_tatsu_b = entry[4].parse::<i32>().unwrap();
+        let num_mentsu_a = entry[1].parse::<u8>().unwrap();
+        let num_tatsu_a = entry[2].parse::<u8>().unwrap();
+        let num_mentsu_b = entry[3].parse::<u8>().unwrap();
+        let num_tatsu_b = entry[4].parse::<u8>().unwrap();
         let (num_mentsu, num_tatsu) =
             if (2 * num_mentsu_a + num_tatsu_a) >= (2 * num_mentsu_b + num_tatsu_b) {
                 (num_mentsu_a, num_tatsu_a)
             } else {
                 (num_mentsu_b, num_tatsu_b)
             };
-        let value_str = format!("({}, {})", num_mentsu, num_tatsu);
-        builder.entry(entry[0].to_owned(), &value_str);
+        // 一萬側を最上位桁とする5進数
+        let index = entry[0]
+            .bytes()
+            .fold(0, |key, c| key * 5 + (c - b'0') as usize);
+        table[index] = (num_mentsu << 4) | num_tatsu;
     }
 
-    // phfでハッシュテーブル出力
-    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("shanten_hash_table.rs");
+    // テーブル本体はバイナリで出力して取り込む
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let table_path = Path::new(&out_dir).join("shanten_table.bin");
+    File::create(&table_path)
+        .unwrap()
+        .write_all(&table)
+        .unwrap();
+
+    let path = Path::new(&out_dir).join("shanten_table.rs");
     let mut outfile = BufWriter::new(File::create(&path).unwrap());
     writeln!(
         &mut outfile,
-        "static SHANTEN_HASH: phf::Map<&'static str, (i32, i32)> = \n{};\n",
-        builder.build()
+        "static SHANTEN_TABLE: &[u8; {}] = include_bytes!(concat!(env!(\"OUT_DIR\"), \"/shanten_table.bin\"));\n",
+        SUHAI_TABLE_SIZE
+    )
+    .unwrap();
+
+    // 字牌は刻子と対子だけ数えればOK（枚数ごとの面子数, 塔子数）
+    let jihai_table: Vec<String> = (0..5)
+        .map(|c| match c {
+            2 => "(0, 1)",
+            3 | 4 => "(1, 0)",
+            _ => "(0, 0)",
+        })
+        .map(String::from)
+        .collect();
+    writeln!(
+        &mut outfile,
+        "static JIHAI_SHANTEN_TABLE: [(i32, i32); 5] = [{}];",
+        jihai_table.join(", ")
     )
     .unwrap();
 }
@@ -0,0 +1,422 @@
+use crate::kyoku::*;
+use crate::score::*;
+use crate::settlement::*;
+use crate::types::*;
+use crate::wall::*;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// 対局の長さ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameLength {
+    /// 東風戦
+    Tonpuusen,
+    /// 半荘戦
+    Hanchan,
+}
+
+/// 対局ルール
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameConfig {
+    /// 対局の長さ
+    pub length: GameLength,
+    /// 配給原点
+    pub initial_score: i32,
+    /// 返し点（延長と和了止めの基準）
+    pub target_score: i32,
+    /// 0点未満で終了（トビ）する？
+    pub tobi: bool,
+    /// 規定の局が終わって誰も返し点に達していなければ次の場に延長する？（西入/南入）
+    pub extension: bool,
+    /// オーラスの親が和了/聴牌して1位かつ返し点以上なら終了する？（和了止め）
+    pub agariyame: bool,
+    /// 流局時に親が聴牌していれば連荘する？
+    pub tenpai_renchan: bool,
+    /// 牌山の設定
+    pub wall: WallConfig,
+    /// 得点計算ルール
+    pub rule: ScoreRuleConfig,
+    /// 途中流局ルール
+    pub abortive_draw: AbortiveDrawConfig,
+    /// 複数人のロンの扱い
+    pub multiple_ron: MultipleRonRule,
+}
+
+/// 対局（半荘/東風戦）の進行と点数の管理
+///
+/// 起家は席0。局の結果を適用するごとに持ち点の移動、親の移動、本場と供託立直棒を更新する。
+/// 終了時に残った供託立直棒は1位が受け取る（同点は起家に近い席を上位とする）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    config: GameConfig,
+    scores: [i32; 4],
+    round: Wind,
+    // 場の中の局数（0から3, 親の席に一致）
+    kyoku: usize,
+    nhonba: i32,
+    nriichi: i32,
+    finished: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            length: GameLength::Hanchan,
+            initial_score: 25000,
+            target_score: 30000,
+            tobi: true,
+            extension: true,
+            agariyame: true,
+            tenpai_renchan: true,
+            wall: WallConfig::default(),
+            rule: ScoreRuleConfig::default(),
+            abortive_draw: AbortiveDrawConfig::default(),
+            multiple_ron: MultipleRonRule::default(),
+        }
+    }
+}
+
+// 次の場
+fn next_round(round: Wind) -> Wind {
+    match round {
+        Wind::Ton => Wind::Nan,
+        Wind::Nan => Wind::Sha,
+        Wind::Sha => Wind::Pee,
+        Wind::Pee => Wind::Ton,
+    }
+}
+
+impl GameLength {
+    /// 規定の最後の場
+    pub fn last_round(&self) -> Wind {
+        match self {
+            GameLength::Tonpuusen => Wind::Ton,
+            GameLength::Hanchan => Wind::Nan,
+        }
+    }
+}
+
+impl Game {
+    /// 東1局0本場から対局を開始
+    pub fn new(config: &GameConfig) -> Self {
+        Game {
+            config: *config,
+            scores: [config.initial_score; 4],
+            round: Wind::Ton,
+            kyoku: 0,
+            nhonba: 0,
+            nriichi: 0,
+            finished: false,
+        }
+    }
+
+    /// 対局ルール
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// 各席の持ち点
+    pub fn scores(&self) -> [i32; 4] {
+        self.scores
+    }
+
+    /// 場風
+    pub fn round(&self) -> Wind {
+        self.round
+    }
+
+    /// 場の中の局数（0から3）
+    pub fn kyoku_index(&self) -> usize {
+        self.kyoku
+    }
+
+    /// 親の席
+    pub fn dealer(&self) -> usize {
+        self.kyoku
+    }
+
+    /// 本場
+    pub fn nhonba(&self) -> i32 {
+        self.nhonba
+    }
+
+    /// 供託立直棒数
+    pub fn nriichi(&self) -> i32 {
+        self.nriichi
+    }
+
+    /// 対局が終了したか？
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 順位順の席（同点は起家に近い席を上位とする）
+    pub fn ranking(&self) -> [usize; 4] {
+        let mut seats = [0, 1, 2, 3];
+        seats.sort_by_key(|s| -self.scores[*s]);
+        seats
+    }
+
+    /// 現在の局の設定
+    pub fn kyoku_config(&self) -> KyokuConfig {
+        KyokuConfig {
+            round: self.round,
+            dealer: self.kyoku,
+            nhonba: self.nhonba,
+            nriichi: self.nriichi,
+            scores: self.scores,
+            wall: self.config.wall,
+            rule: self.config.rule,
+            abortive_draw: self.config.abortive_draw,
+            multiple_ron: self.config.multiple_ron,
+        }
+    }
+
+    /// シードを指定して現在の局を開始
+    pub fn start_kyoku(&self, seed: u64) -> Result<Kyoku, Error> {
+        if self.finished {
+            return Err(Error::from("Cannot to start kyoku: game is finished."));
+        }
+        Kyoku::new(&self.kyoku_config(), seed)
+    }
+
+    /// 終局した局の結果を適用し、各席の点数の増減を返す
+    pub fn apply_kyoku_result(&mut self, kyoku: &Kyoku) -> Result<[i32; 4], Error> {
+        let riichi = kyoku.players().clone().map(|p| p.riichi);
+        match kyoku.result() {
+            Some(KyokuResult::Agari(results)) => match results.as_slice() {
+                [agari] => self.apply_agari(agari.winner, agari.loser, &agari.score, riichi),
+                [first, ..] => match first.loser {
+                    Some(loser) => {
+                        let wins: Vec<(usize, &Score)> =
+                            results.iter().map(|a| (a.winner, &a.score)).collect();
+                        self.apply_multiple_ron(loser, &wins, riichi)
+                    }
+                    None => Err(Error::from("Invalid agari: multiple winners must be ron.")),
+                },
+                [] => Err(Error::from("Invalid agari: no winner.")),
+            },
+            Some(KyokuResult::Ryukyoku { tenpai }) => {
+                let discards = kyoku.players().each_ref().map(|p| p.discards.as_slice());
+                let settlement =
+                    settle_exhaustive_draw(*tenpai, discards, self.dealer(), &self.config.rule);
+                self.apply_ryukyoku_settlement(&settlement, riichi)
+            }
+            Some(KyokuResult::AbortiveDraw(_)) => self.apply_abortive_draw(riichi),
+            None => Err(Error::from(
+                "Cannot to apply kyoku result: kyoku is not finished.",
+            )),
+        }
+    }
+
+    /// 和了を適用し、各席の点数の増減を返す
+    ///
+    /// riichiはこの局で立直した席。和了者は支払いと供託立直棒（この局の立直を含む）を受け取る。
+    pub fn apply_agari(
+        &mut self,
+        winner: usize,
+        loser: Option<usize>,
+        score: &Score,
+        riichi: [bool; 4],
+    ) -> Result<[i32; 4], Error> {
+        self.check_not_finished()?;
+        if winner >= 4 || loser.is_some_and(|l| l >= 4 || l == winner) {
+            return Err(Error::from("Invalid agari: invalid winner or loser seat."));
+        }
+
+        // 支払い
+        let mut payments = [0; 4];
+        match (&score.point.feed, loser) {
+            (Feed::Duck { point }, Some(l)) => payments[l] = *point,
+            (Feed::Tsumo { ko, oya }, None) => {
+                for (s, p) in payments.iter_mut().enumerate() {
+                    if s != winner {
+                        *p = if s == self.dealer() { *oya } else { *ko };
+                    }
+                }
+            }
+            _ => {
+                return Err(Error::from(
+                    "Invalid agari: payment does not match with tsumo or ron.",
+                ));
+            }
+        }
+
+        let mut deltas = self.deposit_riichi(riichi);
+        for (d, p) in deltas.iter_mut().zip(payments) {
+            *d -= p;
+        }
+        deltas[winner] += payments.iter().sum::<i32>();
+        deltas[winner] += 1000 * self.nriichi;
+        self.nriichi = 0;
+        self.apply_deltas(&deltas);
+
+        let renchan = winner == self.dealer();
+        self.advance(renchan, renchan);
+        Ok(deltas)
+    }
+
+    /// 複数人のロンを適用し、各席の点数の増減を返す
+    ///
+    /// winsは和了者と現在の本場で計算した得点計算結果の組。riichiはこの局で立直した席。
+    /// settle_ronで精算し（本場と供託は放銃者の下家から最も近い和了者）、親が和了すれば連荘する。
+    pub fn apply_multiple_ron(
+        &mut self,
+        loser: usize,
+        wins: &[(usize, &Score)],
+        riichi: [bool; 4],
+    ) -> Result<[i32; 4], Error> {
+        self.check_not_finished()?;
+        let nriichi = self.nriichi + riichi.iter().filter(|r| **r).count() as i32;
+        let settlement = settle_ron(
+            loser,
+            wins,
+            self.nhonba,
+            nriichi,
+            &self.config.rule,
+            MultipleRonRule::MultipleRon,
+            false,
+        )?
+        .unwrap();
+        let mut deltas = self.deposit_riichi(riichi);
+        for (d, s) in deltas.iter_mut().zip(settlement.deltas) {
+            *d += s;
+        }
+        self.nriichi = 0;
+        self.apply_deltas(&deltas);
+
+        let renchan = settlement.winners.contains(&self.dealer());
+        self.advance(renchan, renchan);
+        Ok(deltas)
+    }
+
+    /// 流局を適用し、各席の点数の増減を返す（流し満貫は考慮しない）
+    ///
+    /// riichiはこの局で立直した席。不聴罰符を精算し、立直棒は供託として次局に持ち越す。
+    pub fn apply_ryukyoku(
+        &mut self,
+        tenpai: [bool; 4],
+        riichi: [bool; 4],
+    ) -> Result<[i32; 4], Error> {
+        let settlement = RyukyokuSettlement {
+            tenpai,
+            nagashimangan: vec![],
+            deltas: calculate_noten_penalty(tenpai),
+        };
+        self.apply_ryukyoku_settlement(&settlement, riichi)
+    }
+
+    /// 精算済みの荒牌流局を適用し、各席の点数の増減を返す
+    ///
+    /// riichiはこの局で立直した席。立直棒は供託として次局に持ち越す。
+    pub fn apply_ryukyoku_settlement(
+        &mut self,
+        settlement: &RyukyokuSettlement,
+        riichi: [bool; 4],
+    ) -> Result<[i32; 4], Error> {
+        self.check_not_finished()?;
+        let mut deltas = self.deposit_riichi(riichi);
+        for (d, s) in deltas.iter_mut().zip(settlement.deltas) {
+            *d += s;
+        }
+        self.apply_deltas(&deltas);
+        let renchan = self.config.tenpai_renchan && settlement.tenpai[self.dealer()];
+        self.advance(renchan, true);
+        Ok(deltas)
+    }
+
+    /// 途中流局を適用し、各席の点数の増減を返す
+    ///
+    /// 点数の移動はなく、親が連荘して1本場を積む。立直棒は供託として次局に持ち越す。
+    pub fn apply_abortive_draw(&mut self, riichi: [bool; 4]) -> Result<[i32; 4], Error> {
+        self.check_not_finished()?;
+        let deltas = self.deposit_riichi(riichi);
+        self.apply_deltas(&deltas);
+        // 和了止めの対象外で、立直棒の供託では終了条件を満たさない
+        self.nhonba += 1;
+        Ok(deltas)
+    }
+
+    fn check_not_finished(&self) -> Result<(), Error> {
+        if self.finished {
+            return Err(Error::from(
+                "Cannot to apply kyoku result: game is finished.",
+            ));
+        }
+        Ok(())
+    }
+
+    // 立直棒を供託に出す
+    fn deposit_riichi(&mut self, riichi: [bool; 4]) -> [i32; 4] {
+        let mut deltas = [0; 4];
+        for (d, r) in deltas.iter_mut().zip(riichi) {
+            if r {
+                *d -= 1000;
+                self.nriichi += 1;
+            }
+        }
+        deltas
+    }
+
+    fn apply_deltas(&mut self, deltas: &[i32; 4]) {
+        for (s, d) in self.scores.iter_mut().zip(deltas) {
+            *s += d;
+        }
+    }
+
+    // 次の局へ進め、終了条件を判定
+    fn advance(&mut self, renchan: bool, add_honba: bool) {
+        let last_round = self.config.length.last_round();
+        let extension_round = next_round(last_round);
+        let is_all_last = self.round == last_round && self.kyoku == 3;
+        let reached_target = self.scores.iter().any(|s| *s >= self.config.target_score);
+
+        // トビ
+        if self.config.tobi && self.scores.iter().any(|s| *s < 0) {
+            return self.finish();
+        }
+        // 延長戦は返し点に達した時点で終了
+        if self.round == extension_round && reached_target {
+            return self.finish();
+        }
+
+        self.nhonba = if add_honba { self.nhonba + 1 } else { 0 };
+        if renchan {
+            // 和了止め/聴牌止め
+            let dealer = self.dealer();
+            if self.config.agariyame
+                && is_all_last
+                && self.ranking()[0] == dealer
+                && self.scores[dealer] >= self.config.target_score
+            {
+                return self.finish();
+            }
+            return;
+        }
+
+        // 親の移動
+        self.kyoku += 1;
+        if self.kyoku < 4 {
+            return;
+        }
+        self.kyoku = 0;
+        if self.round == extension_round
+            || (self.round == last_round && (reached_target || !self.config.extension))
+        {
+            return self.finish();
+        }
+        self.round = next_round(self.round);
+    }
+
+    // 終了し、残った供託立直棒を1位に渡す
+    fn finish(&mut self) {
+        let top = self.ranking()[0];
+        self.scores[top] += 1000 * self.nriichi;
+        self.nriichi = 0;
+        self.finished = true;
+    }
+}
@@ -0,0 +1,1002 @@
+use crate::score::*;
+use crate::settlement::*;
+use crate::shanten::*;
+use crate::types::*;
+use crate::wall::*;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// 局の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KyokuConfig {
+    /// 場風
+    pub round: Wind,
+    /// 親の席（0から3）
+    pub dealer: usize,
+    /// 本場
+    pub nhonba: i32,
+    /// 局の開始時に供託されている立直棒数
+    pub nriichi: i32,
+    /// 局の開始時の各席の持ち点（立直には1000点以上必要）
+    pub scores: [i32; 4],
+    /// 牌山の設定
+    pub wall: WallConfig,
+    /// 得点計算ルール
+    pub rule: ScoreRuleConfig,
+    /// 途中流局ルール
+    pub abortive_draw: AbortiveDrawConfig,
+    /// 複数人のロンの扱い
+    pub multiple_ron: MultipleRonRule,
+}
+
+/// プレイヤーの状態
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerState {
+    /// 手牌（手番中は自摸牌を含む）
+    pub hand: Hand,
+    /// 河
+    pub discards: Vec<DiscardedTile>,
+    /// 立直している？
+    pub riichi: bool,
+    /// ダブル立直？
+    pub doubleriichi: bool,
+    /// 一発の権利がある？
+    pub ippatsu: bool,
+    /// 同巡内フリテン（和了牌を見逃してから自分の打牌まで）
+    pub temporary_furiten: bool,
+    /// 立直後の見逃しによるフリテン
+    pub riichi_furiten: bool,
+}
+
+/// 局中の行動
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /// 打牌（riichiが真なら立直宣言）
+    Discard { tile: Tile, riichi: bool },
+    /// 自摸和了
+    Tsumo,
+    /// 暗槓
+    Ankan { tile: TileId },
+    /// 加槓
+    Kakan { tile: TileId },
+    /// ロン（打牌または加槓した牌に対して）
+    Ron,
+    /// ポン（手牌から晒す2枚）
+    Pon { tiles: [Tile; 2] },
+    /// チー（手牌から晒す2枚）
+    Chi { tiles: [Tile; 2] },
+    /// 大明槓
+    Minkan,
+    /// 見送り
+    Pass,
+    /// 九種九牌
+    KyuushuKyuuhai,
+}
+
+/// 局の進行状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// 手番のプレイヤーの行動待ち
+    Turn { seat: usize },
+    /// 打牌に対する他家の宣言待ち
+    Discarded { seat: usize, tile: Tile },
+    /// 加槓に対する搶槓の宣言待ち
+    Kakan { seat: usize, tile: Tile },
+    /// 終局
+    Finished,
+}
+
+/// 和了の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AgariResult {
+    /// 和了者の席
+    pub winner: usize,
+    /// 放銃者の席（自摸ならNone）
+    pub loser: Option<usize>,
+    /// 和了時の状況
+    pub info: AgariInformation,
+    /// 得点計算結果
+    pub score: Score,
+}
+
+/// 局の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KyokuResult {
+    /// 和了（複数人のロンは放銃者の下家から順。精算はsettle_ronで行う）
+    Agari(Vec<AgariResult>),
+    /// 荒牌流局（各席の聴牌状態）
+    Ryukyoku { tenpai: [bool; 4] },
+    /// 途中流局
+    AbortiveDraw(AbortiveDraw),
+}
+
+/// 局（配牌から和了/流局まで）
+///
+/// 手番のプレイヤーと宣言できるプレイヤーがactで行動し、自摸は自動で行う。
+/// 打牌に対しては宣言できるプレイヤー全員の応答を待ち、ロン > ポン/大明槓 > チーの
+/// 優先順で解決する。複数のロンはMultipleRonRuleに従う。
+/// 途中流局はAbortiveDrawConfigで有効にしたものを判定する。
+/// 槓ドラは暗槓では即座に、大明槓/加槓では次の打牌時にめくる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kyoku {
+    config: KyokuConfig,
+    wall: Wall,
+    players: [PlayerState; 4],
+    phase: Phase,
+    // 手番の自摸牌（鳴いた後はNone）
+    drawn: Option<Tile>,
+    // 自摸牌が嶺上牌か？
+    rinshan: bool,
+    // 局中の副露と暗槓の回数
+    num_calls: usize,
+    // 次の打牌でめくる槓ドラの数
+    pending_dora: usize,
+    // 喰い替えで打牌できない牌
+    kuikae: Vec<TileId>,
+    // 立直宣言した打牌の席（打牌が通ると成立）
+    pending_riichi: Option<usize>,
+    // 宣言待ちの応答（Noneは未応答）
+    responses: [Option<Action>; 4],
+    // 行動の記録
+    log: Vec<(usize, Action)>,
+    result: Option<KyokuResult>,
+}
+
+impl Default for KyokuConfig {
+    fn default() -> Self {
+        KyokuConfig {
+            round: Wind::Ton,
+            dealer: 0,
+            nhonba: 0,
+            nriichi: 0,
+            scores: [25000; 4],
+            wall: WallConfig::default(),
+            rule: ScoreRuleConfig::default(),
+            abortive_draw: AbortiveDrawConfig::default(),
+            multiple_ron: MultipleRonRule::default(),
+        }
+    }
+}
+
+impl PlayerState {
+    fn new(hand: Vec<Tile>) -> Self {
+        PlayerState {
+            hand: Hand {
+                hand,
+                melds: vec![],
+            },
+            discards: vec![],
+            riichi: false,
+            doubleriichi: false,
+            ippatsu: false,
+            temporary_furiten: false,
+            riichi_furiten: false,
+        }
+    }
+}
+
+// 鳴いた席から見た副露元
+fn called_from(caller: usize, discarder: usize) -> CalledFrom {
+    match (discarder + 4 - caller) % 4 {
+        3 => CalledFrom::Kamicha,
+        2 => CalledFrom::Toimen,
+        _ => CalledFrom::Shimocha,
+    }
+}
+
+// チーした後に打牌できない牌（鳴いた牌と筋の牌）
+fn chi_kuikae_tiles(called: TileId, tiles: [Tile; 2]) -> Vec<TileId> {
+    let mut kuikae = vec![called];
+    let n = called.getnumber();
+    if tiles.iter().all(|t| t.id > called) && n <= 6 {
+        kuikae.push(called.nth(3));
+    } else if tiles.iter().all(|t| t.id < called) && n >= 4 {
+        kuikae.push(called.nth(-3));
+    }
+    kuikae
+}
+
+// 鳴いた後に喰い替えにならない打牌が残るか？
+fn can_discard_after_call(hand: &[Tile], tiles: [Tile; 2], kuikae: &[TileId]) -> bool {
+    let mut remain = hand.to_vec();
+    for t in tiles {
+        remove_tile(&mut remain, t);
+    }
+    remain.iter().any(|t| !kuikae.contains(&t.id))
+}
+
+// 手牌から指定した牌を1枚取り除く
+fn remove_tile(hand: &mut Vec<Tile>, tile: Tile) -> bool {
+    match hand.iter().position(|t| *t == tile) {
+        Some(index) => {
+            hand.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+// 聴牌形（副露を含めて13枚）の待ち牌
+fn waiting_tile_ids(hand: &Hand) -> Vec<TileId> {
+    listup_waiting_tiles(hand)
+        .map(|wts| wts.iter().map(|wt| wt.tile).collect())
+        .unwrap_or_default()
+}
+
+// 牌の組み合わせを重複なく列挙（赤ドラの有無で区別）
+fn distinct_tiles(hand: &[Tile], id: TileId) -> Vec<Tile> {
+    let mut tiles: Vec<Tile> = vec![];
+    for t in hand.iter().filter(|t| t.id == id) {
+        if !tiles.contains(t) {
+            tiles.push(*t);
+        }
+    }
+    tiles
+}
+
+impl Kyoku {
+    /// シードから牌山を積んで配牌し、親の最初の自摸まで進める
+    pub fn new(config: &KyokuConfig, seed: u64) -> Result<Self, Error> {
+        Self::from_wall(config, Wall::new(&config.wall, seed)?)
+    }
+
+    /// 積んだ牌山から配牌し、親の最初の自摸まで進める
+    pub fn from_wall(config: &KyokuConfig, mut wall: Wall) -> Result<Self, Error> {
+        if config.dealer >= 4 {
+            return Err(Error::from("Invalid kyoku config: dealer must be 0 to 3."));
+        }
+        // 配牌は親から順に配られる
+        let hands = wall.deal()?;
+        let players = std::array::from_fn(|seat| {
+            PlayerState::new(hands[(seat + 4 - config.dealer) % 4].clone())
+        });
+        let mut kyoku = Kyoku {
+            config: *config,
+            wall,
+            players,
+            phase: Phase::Finished,
+            drawn: None,
+            rinshan: false,
+            num_calls: 0,
+            pending_dora: 0,
+            kuikae: vec![],
+            pending_riichi: None,
+            responses: [None; 4],
+            log: vec![],
+            result: None,
+        };
+        kyoku.start_turn(config.dealer, false);
+        Ok(kyoku)
+    }
+
+    /// シードと行動の記録から局を再現
+    pub fn replay(config: &KyokuConfig, seed: u64, log: &[(usize, Action)]) -> Result<Self, Error> {
+        let mut kyoku = Self::new(config, seed)?;
+        for (seat, action) in log {
+            kyoku.act(*seat, *action)?;
+        }
+        Ok(kyoku)
+    }
+
+    /// 局の設定
+    pub fn config(&self) -> &KyokuConfig {
+        &self.config
+    }
+
+    /// 牌山
+    pub fn wall(&self) -> &Wall {
+        &self.wall
+    }
+
+    /// 各席のプレイヤーの状態
+    pub fn players(&self) -> &[PlayerState; 4] {
+        &self.players
+    }
+
+    /// 局の進行状況
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// 手番の自摸牌（鳴いた後や手番以外ではNone）
+    pub fn drawn_tile(&self) -> Option<Tile> {
+        self.drawn
+    }
+
+    /// 行動の記録
+    pub fn log(&self) -> &[(usize, Action)] {
+        &self.log
+    }
+
+    /// 局の結果（終局前はNone）
+    pub fn result(&self) -> Option<&KyokuResult> {
+        self.result.as_ref()
+    }
+
+    /// 供託されている立直棒数（この局で成立した立直を含む）
+    pub fn nriichi(&self) -> i32 {
+        self.config.nriichi + self.players.iter().filter(|p| p.riichi).count() as i32
+    }
+
+    /// 席の自風
+    pub fn seat_wind(&self, seat: usize) -> Wind {
+        [Wind::Ton, Wind::Nan, Wind::Sha, Wind::Pee][(seat + 4 - self.config.dealer) % 4]
+    }
+
+    /// フリテンか？（捨て牌、同巡内、立直後の見逃し）
+    pub fn is_furiten(&self, seat: usize) -> bool {
+        let player = &self.players[seat];
+        if player.temporary_furiten || player.riichi_furiten {
+            return true;
+        }
+        let waits = waiting_tile_ids(&player.hand);
+        player.discards.iter().any(|d| waits.contains(&d.tile.id))
+    }
+
+    /// 席のプレイヤーが取れる行動を列挙（行動できなければ空）
+    pub fn legal_actions(&self, seat: usize) -> Vec<Action> {
+        match self.phase {
+            Phase::Turn { seat: s } if s == seat => self.legal_turn_actions(seat),
+            Phase::Discarded { seat: s, tile } if s != seat && self.responses[seat].is_none() => {
+                self.legal_call_actions(seat, s, tile)
+            }
+            Phase::Kakan { seat: s, tile } if s != seat && self.responses[seat].is_none() => {
+                let mut actions = vec![];
+                if self.evaluate_ron(seat, s, tile, true).is_some() {
+                    actions.push(Action::Ron);
+                }
+                actions.push(Action::Pass);
+                actions
+            }
+            _ => vec![],
+        }
+    }
+
+    /// 席のプレイヤーの行動を適用
+    pub fn act(&mut self, seat: usize, action: Action) -> Result<(), Error> {
+        if seat >= 4 || !self.legal_actions(seat).contains(&action) {
+            return Err(Error::from(format!(
+                "Invalid action: {:?} is not allowed for seat {} in {:?}.",
+                action, seat, self.phase
+            )));
+        }
+        self.log.push((seat, action));
+        match self.phase {
+            Phase::Turn { .. } => self.apply_turn_action(seat, action),
+            Phase::Discarded {
+                seat: discarder,
+                tile,
+            } => {
+                self.responses[seat] = Some(action);
+                if self.responses.iter().all(|r| r.is_some()) {
+                    self.resolve_discard(discarder, tile);
+                }
+            }
+            Phase::Kakan {
+                seat: kakan_seat,
+                tile,
+            } => {
+                self.responses[seat] = Some(action);
+                if self.responses.iter().all(|r| r.is_some()) {
+                    self.resolve_kakan(kakan_seat, tile);
+                }
+            }
+            Phase::Finished => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // 手番を開始（自摸する場合は自摸牌を加える）
+    fn start_turn(&mut self, seat: usize, rinshan: bool) {
+        let tile = if rinshan {
+            self.wall.draw_rinshan()
+        } else {
+            self.wall.draw()
+        };
+        let tile = tile.expect("wall must have a tile to draw");
+        self.players[seat].hand.hand.push(tile);
+        self.drawn = Some(tile);
+        self.rinshan = rinshan;
+        self.kuikae.clear();
+        self.phase = Phase::Turn { seat };
+    }
+
+    // 鳴いた後の手番を開始
+    fn start_called_turn(&mut self, seat: usize, kuikae: Vec<TileId>) {
+        self.drawn = None;
+        self.rinshan = false;
+        self.kuikae = kuikae;
+        self.phase = Phase::Turn { seat };
+    }
+
+    // 宣言待ちを開始（宣言できないプレイヤーは見送りとする）
+    fn start_responses(&mut self, seat: usize, phase: Phase) {
+        self.phase = phase;
+        self.responses = [None; 4];
+        for s in 0..4 {
+            if s == seat || self.legal_actions(s) == [Action::Pass] {
+                self.responses[s] = Some(Action::Pass);
+            }
+        }
+        match phase {
+            Phase::Discarded { tile, .. } if self.responses.iter().all(|r| r.is_some()) => {
+                self.resolve_discard(seat, tile)
+            }
+            Phase::Kakan { tile, .. } if self.responses.iter().all(|r| r.is_some()) => {
+                self.resolve_kakan(seat, tile)
+            }
+            _ => {}
+        }
+    }
+
+    // 手番のプレイヤーが取れる行動
+    fn legal_turn_actions(&self, seat: usize) -> Vec<Action> {
+        let player = &self.players[seat];
+        let mut actions = vec![];
+
+        // 自摸和了
+        if let Some(drawn) = self.drawn {
+            if self.evaluate_tsumo(seat, drawn).is_some() {
+                actions.push(Action::Tsumo);
+            }
+        }
+
+        // 九種九牌（第一巡の鳴きがない自摸で么九牌が9種以上）
+        if self.config.abortive_draw.kyuushu_kyuuhai
+            && self.drawn.is_some()
+            && self.num_calls == 0
+            && player.discards.is_empty()
+        {
+            let mut yaochu: Vec<TileId> = player
+                .hand
+                .hand
+                .iter()
+                .map(|t| t.id)
+                .filter(|t| t.is_yaochu())
+                .collect();
+            yaochu.sort_by_key(|t| *t as i32);
+            yaochu.dedup();
+            if yaochu.len() >= 9 {
+                actions.push(Action::KyuushuKyuuhai);
+            }
+        }
+
+        // 打牌（立直後は自摸切りのみ）
+        let mut candidates: Vec<Tile> = vec![];
+        for t in &player.hand.hand {
+            if !candidates.contains(t) && !self.kuikae.contains(&t.id) {
+                candidates.push(*t);
+            }
+        }
+        if player.riichi {
+            candidates.retain(|t| Some(*t) == self.drawn);
+        }
+        let can_riichi = !player.riichi
+            && self.config.scores[seat] >= 1000
+            && self.drawn.is_some()
+            && player.hand.is_menzen()
+            && self.wall.num_remaining() >= 4;
+        for t in candidates {
+            actions.push(Action::Discard {
+                tile: t,
+                riichi: false,
+            });
+            if can_riichi {
+                let mut remain = player.hand.clone();
+                remove_tile(&mut remain.hand, t);
+                if calculate_hand_shanten(&remain).ok() == Some(0) {
+                    actions.push(Action::Discard {
+                        tile: t,
+                        riichi: true,
+                    });
+                }
+            }
+        }
+
+        // 槓（自摸した後のみ, 海底牌と5回目の槓は不可）
+        let drawn = match self.drawn {
+            Some(t) => t,
+            None => return actions,
+        };
+        if self.wall.num_remaining() == 0 || self.wall.num_rinshan() >= NUM_RINSHAN_TILES {
+            return actions;
+        }
+        let counts =
+            TileCountArray::from_tiles(&player.hand.hand.iter().map(|t| t.id).collect::<Vec<_>>());
+        for (t, c) in counts.iter() {
+            if c == 4 && (!player.riichi || self.can_ankan_after_riichi(seat, t, drawn)) {
+                actions.push(Action::Ankan { tile: t });
+            }
+        }
+        for m in &player.hand.melds {
+            if let Meld::Pung { tiles, .. } = m {
+                if counts[tiles[0].id] > 0 {
+                    actions.push(Action::Kakan { tile: tiles[0].id });
+                }
+            }
+        }
+
+        actions
+    }
+
+    // 立直後の暗槓は自摸牌で槓し、待ちが変わらない場合のみ
+    fn can_ankan_after_riichi(&self, seat: usize, t: TileId, drawn: Tile) -> bool {
+        if drawn.id != t {
+            return false;
+        }
+        let mut before = self.players[seat].hand.clone();
+        remove_tile(&mut before.hand, drawn);
+        let mut after = self.players[seat].hand.clone();
+        let tiles: Vec<Tile> = after.hand.iter().filter(|x| x.id == t).cloned().collect();
+        after.hand.retain(|x| x.id != t);
+        after.melds.push(Meld::Ankan {
+            tiles: [tiles[0], tiles[1], tiles[2], tiles[3]],
+        });
+        waiting_tile_ids(&before) == waiting_tile_ids(&after)
+    }
+
+    // 打牌に対して宣言できる行動
+    fn legal_call_actions(&self, seat: usize, discarder: usize, tile: Tile) -> Vec<Action> {
+        let player = &self.players[seat];
+        let mut actions = vec![];
+        if self.evaluate_ron(seat, discarder, tile, false).is_some() {
+            actions.push(Action::Ron);
+        }
+
+        // 立直中と河底牌は鳴けない
+        if !player.riichi && self.wall.num_remaining() > 0 {
+            let hand = &player.hand.hand;
+            let num_same = hand.iter().filter(|t| t.id == tile.id).count();
+            // ポン（鳴いた後に喰い替えにならない打牌が残る場合のみ、チーも同様）
+            if num_same >= 2 {
+                let same = distinct_tiles(hand, tile.id);
+                for (i, a) in same.iter().enumerate() {
+                    for b in &same[i..] {
+                        let enough = a != b || hand.iter().filter(|t| *t == a).count() >= 2;
+                        if enough && can_discard_after_call(hand, [*a, *b], &[tile.id]) {
+                            actions.push(Action::Pon { tiles: [*a, *b] });
+                        }
+                    }
+                }
+            }
+            // 大明槓
+            if num_same == 3 && self.wall.num_rinshan() < NUM_RINSHAN_TILES {
+                actions.push(Action::Minkan);
+            }
+            // チー（上家からのみ）
+            if seat == (discarder + 1) % 4 && tile.id.is_suhai() {
+                let n = tile.id.getnumber();
+                for (da, db) in [(-2, -1), (-1, 1), (1, 2)] {
+                    if !(1..=9).contains(&(n + da)) || !(1..=9).contains(&(n + db)) {
+                        continue;
+                    }
+                    for a in distinct_tiles(hand, tile.id.nth(da)) {
+                        for b in distinct_tiles(hand, tile.id.nth(db)) {
+                            let kuikae = chi_kuikae_tiles(tile.id, [a, b]);
+                            if can_discard_after_call(hand, [a, b], &kuikae) {
+                                actions.push(Action::Chi { tiles: [a, b] });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        actions.push(Action::Pass);
+        actions
+    }
+
+    // 手番の行動を適用
+    fn apply_turn_action(&mut self, seat: usize, action: Action) {
+        match action {
+            Action::Discard { tile, riichi } => self.discard(seat, tile, riichi),
+            Action::Tsumo => {
+                let drawn = self.drawn.unwrap();
+                let (info, score) = self.evaluate_tsumo(seat, drawn).unwrap();
+                self.finish(KyokuResult::Agari(vec![AgariResult {
+                    winner: seat,
+                    loser: None,
+                    info,
+                    score,
+                }]));
+            }
+            Action::Ankan { tile } => {
+                let player = &mut self.players[seat];
+                let tiles: Vec<Tile> = player
+                    .hand
+                    .hand
+                    .iter()
+                    .filter(|t| t.id == tile)
+                    .cloned()
+                    .collect();
+                player.hand.hand.retain(|t| t.id != tile);
+                player.hand.melds.push(Meld::Ankan {
+                    tiles: [tiles[0], tiles[1], tiles[2], tiles[3]],
+                });
+                self.interrupt();
+                self.wall.flip_dora();
+                self.start_turn(seat, true);
+            }
+            Action::KyuushuKyuuhai => {
+                self.finish(KyokuResult::AbortiveDraw(AbortiveDraw::KyuushuKyuuhai {
+                    seat,
+                }));
+            }
+            Action::Kakan { tile } => {
+                let player = &mut self.players[seat];
+                let added = *player.hand.hand.iter().find(|t| t.id == tile).unwrap();
+                remove_tile(&mut player.hand.hand, added);
+                self.drawn = None;
+                self.start_responses(seat, Phase::Kakan { seat, tile: added });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // 打牌
+    fn discard(&mut self, seat: usize, tile: Tile, riichi: bool) {
+        let tsumogiri = self.drawn == Some(tile);
+        let player = &mut self.players[seat];
+        remove_tile(&mut player.hand.hand, tile);
+        player.discards.push(DiscardedTile {
+            tile,
+            tsumogiri,
+            riichi,
+            called: false,
+        });
+        // 自分の打牌で同巡内フリテンと一発は消える
+        player.temporary_furiten = false;
+        player.ippatsu = false;
+        if riichi {
+            self.pending_riichi = Some(seat);
+        }
+        // 大明槓/加槓の槓ドラをめくる
+        for _ in 0..self.pending_dora {
+            self.wall.flip_dora();
+        }
+        self.pending_dora = 0;
+        self.drawn = None;
+        self.start_responses(seat, Phase::Discarded { seat, tile });
+    }
+
+    // 打牌に対する宣言を解決
+    fn resolve_discard(&mut self, discarder: usize, tile: Tile) {
+        let seats: Vec<usize> = (1..4).map(|i| (discarder + i) % 4).collect();
+
+        // ロン（三家和なら途中流局）
+        if self.resolve_ron(discarder, tile, false) {
+            return;
+        }
+
+        // 立直の成立
+        if let Some(s) = self.pending_riichi.take() {
+            let first_discard = self.players[s].discards.len() == 1 && self.num_calls == 0;
+            let player = &mut self.players[s];
+            player.riichi = true;
+            player.doubleriichi = first_discard;
+            player.ippatsu = true;
+        }
+
+        // 和了牌の見逃し
+        self.mark_missed(&seats, tile.id);
+
+        // 四家立直/四風連打/四開槓
+        let rule = self.config.abortive_draw;
+        let abortive = if rule.suucha_riichi && self.players.iter().all(|p| p.riichi) {
+            Some(AbortiveDraw::SuuchaRiichi)
+        } else if rule.suufon_renda && self.is_suufon_renda() {
+            Some(AbortiveDraw::SuufonRenda)
+        } else if rule.suukaikan && self.is_suukaikan() {
+            Some(AbortiveDraw::Suukaikan)
+        } else {
+            None
+        };
+        if let Some(abortive) = abortive {
+            self.finish(KyokuResult::AbortiveDraw(abortive));
+            return;
+        }
+
+        // ポン/大明槓とチー
+        let call = seats
+            .iter()
+            .find(|s| {
+                matches!(
+                    self.responses[**s],
+                    Some(Action::Pon { .. } | Action::Minkan)
+                )
+            })
+            .or_else(|| {
+                seats
+                    .iter()
+                    .find(|s| matches!(self.responses[**s], Some(Action::Chi { .. })))
+            });
+        if let Some(caller) = call {
+            self.call(*caller, discarder, tile);
+            return;
+        }
+
+        // 荒牌流局
+        if self.wall.num_remaining() == 0 {
+            let tenpai = self.tenpai_states();
+            self.finish(KyokuResult::Ryukyoku { tenpai });
+            return;
+        }
+
+        self.start_turn((discarder + 1) % 4, false);
+    }
+
+    // 加槓に対する搶槓を解決
+    fn resolve_kakan(&mut self, seat: usize, tile: Tile) {
+        let seats: Vec<usize> = (1..4).map(|i| (seat + i) % 4).collect();
+        if self.resolve_ron(seat, tile, true) {
+            return;
+        }
+        self.mark_missed(&seats, tile.id);
+
+        // 加槓を成立させて嶺上牌を自摸
+        let player = &mut self.players[seat];
+        for m in player.hand.melds.iter_mut() {
+            if let Meld::Pung { tiles, from } = *m {
+                if tiles[0].id == tile.id {
+                    *m = Meld::Kakan {
+                        tiles: [tiles[0], tiles[1], tiles[2], tile],
+                        from,
+                        added: tile,
+                    };
+                    break;
+                }
+            }
+        }
+        self.interrupt();
+        self.pending_dora += 1;
+        self.start_turn(seat, true);
+    }
+
+    // ロンの宣言を解決し、局が終了したか返す
+    fn resolve_ron(&mut self, loser: usize, tile: Tile, chankan: bool) -> bool {
+        let ron = self.responses.map(|r| r == Some(Action::Ron));
+        if !ron.contains(&true) {
+            return false;
+        }
+        let winners = select_ron_winners(
+            loser,
+            ron,
+            self.config.multiple_ron,
+            self.config.abortive_draw.sanchahou,
+        );
+        let results = match winners {
+            Some(winners) => winners
+                .iter()
+                .map(|winner| {
+                    let (info, score) = self.evaluate_ron(*winner, loser, tile, chankan).unwrap();
+                    AgariResult {
+                        winner: *winner,
+                        loser: Some(loser),
+                        info,
+                        score,
+                    }
+                })
+                .collect(),
+            None => {
+                self.finish(KyokuResult::AbortiveDraw(AbortiveDraw::Sanchahou));
+                return true;
+            }
+        };
+        self.finish(KyokuResult::Agari(results));
+        true
+    }
+
+    // 鳴きのない第一巡で4人が同じ風牌を捨てたか？
+    fn is_suufon_renda(&self) -> bool {
+        let first = match self.players[0].discards.first() {
+            Some(d) => d.tile.id,
+            None => return false,
+        };
+        self.num_calls == 0
+            && (TileId::IdTon..=TileId::IdPee).contains(&first)
+            && self
+                .players
+                .iter()
+                .all(|p| p.discards.len() == 1 && p.discards[0].tile.id == first)
+    }
+
+    // 2人以上で合計4回槓したか？
+    fn is_suukaikan(&self) -> bool {
+        let num_kans: Vec<usize> = self
+            .players
+            .iter()
+            .map(|p| {
+                p.hand
+                    .melds
+                    .iter()
+                    .filter(|m| {
+                        matches!(
+                            m,
+                            Meld::Ankan { .. } | Meld::Minkan { .. } | Meld::Kakan { .. }
+                        )
+                    })
+                    .count()
+            })
+            .collect();
+        num_kans.iter().sum::<usize>() == 4 && !num_kans.contains(&4)
+    }
+
+    // 見逃した和了牌によるフリテンを設定
+    fn mark_missed(&mut self, seats: &[usize], tile: TileId) {
+        for s in seats {
+            if self.responses[*s] == Some(Action::Ron) {
+                continue;
+            }
+            let player = &mut self.players[*s];
+            if waiting_tile_ids(&player.hand).contains(&tile) {
+                if player.riichi {
+                    player.riichi_furiten = true;
+                } else {
+                    player.temporary_furiten = true;
+                }
+            }
+        }
+    }
+
+    // 鳴きの適用
+    fn call(&mut self, caller: usize, discarder: usize, tile: Tile) {
+        let from = Some(called_from(caller, discarder));
+        self.players[discarder].discards.last_mut().unwrap().called = true;
+        let action = self.responses[caller].unwrap();
+        let hand = &mut self.players[caller].hand;
+        match action {
+            Action::Pon { tiles } => {
+                for t in tiles {
+                    remove_tile(&mut hand.hand, t);
+                }
+                hand.melds.push(Meld::Pung {
+                    tiles: [tiles[0], tiles[1], tile],
+                    from,
+                });
+                self.interrupt();
+                self.start_called_turn(caller, vec![tile.id]);
+            }
+            Action::Chi { tiles } => {
+                for t in tiles {
+                    remove_tile(&mut hand.hand, t);
+                }
+                let mut sorted = [tiles[0], tiles[1], tile];
+                sorted.sort_by_key(|t| t.id as i32);
+                hand.melds.push(Meld::Chow {
+                    tiles: [sorted[0], sorted[1], sorted[2]],
+                    from,
+                });
+                self.interrupt();
+                self.start_called_turn(caller, chi_kuikae_tiles(tile.id, tiles));
+            }
+            Action::Minkan => {
+                let tiles: Vec<Tile> = hand
+                    .hand
+                    .iter()
+                    .filter(|t| t.id == tile.id)
+                    .cloned()
+                    .collect();
+                hand.hand.retain(|t| t.id != tile.id);
+                hand.melds.push(Meld::Minkan {
+                    tiles: [tiles[0], tiles[1], tiles[2], tile],
+                    from,
+                });
+                self.interrupt();
+                self.pending_dora += 1;
+                self.start_turn(caller, true);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // 鳴きや槓による一発と第一巡の消滅
+    fn interrupt(&mut self) {
+        self.num_calls += 1;
+        for player in self.players.iter_mut() {
+            player.ippatsu = false;
+        }
+    }
+
+    // 各席の聴牌状態（待ち牌を全て自分で使っている形式聴牌は不聴とする）
+    fn tenpai_states(&self) -> [bool; 4] {
+        let no_dora = Dora {
+            omote: vec![],
+            ura: vec![],
+        };
+        let mut tenpai = [false; 4];
+        for (t, player) in tenpai.iter_mut().zip(self.players.iter()) {
+            *t = calculate_hand_shanten(&player.hand).ok() == Some(0)
+                && count_effective_tiles(&player.hand, &no_dora, &[])
+                    .is_ok_and(|tiles| tiles.iter().any(|(_, n)| *n > 0));
+        }
+        tenpai
+    }
+
+    fn finish(&mut self, result: KyokuResult) {
+        self.phase = Phase::Finished;
+        self.drawn = None;
+        self.result = Some(result);
+    }
+
+    // 和了時の状況を構成
+    fn agari_information(&self, seat: usize, hand: Hand, wining_tile: Tile) -> AgariInformation {
+        let player = &self.players[seat];
+        AgariInformation {
+            wining_tile,
+            hand,
+            nhonba: self.config.nhonba,
+            nriichi: self.nriichi(),
+            round: self.config.round,
+            player: self.seat_wind(seat),
+            tsumo: false,
+            riichi: player.riichi && !player.doubleriichi,
+            ippatsu: player.ippatsu,
+            doubleriichi: player.doubleriichi,
+            haitei: false,
+            rinshan: false,
+            chankan: false,
+            nagashimangan: false,
+            tenho: false,
+            chiho: false,
+            dora: self.wall.dora(player.riichi),
+        }
+    }
+
+    // 役のある和了か判定して得点計算
+    fn evaluate_agari(&self, info: AgariInformation) -> Option<(AgariInformation, Score)> {
+        let score = calculate_score_with_rules(&info, &self.config.rule).ok()?;
+        let has_yaku = score
+            .yaku
+            .iter()
+            .any(|(y, _)| !matches!(y, Yaku::Dora | Yaku::Akadora | Yaku::Uradora));
+        if has_yaku {
+            Some((info, score))
+        } else {
+            None
+        }
+    }
+
+    // 自摸和了の判定
+    fn evaluate_tsumo(&self, seat: usize, drawn: Tile) -> Option<(AgariInformation, Score)> {
+        let player = &self.players[seat];
+        let mut hand = player.hand.clone();
+        remove_tile(&mut hand.hand, drawn);
+        let mut info = self.agari_information(seat, hand, drawn);
+        let first_draw = self.num_calls == 0 && player.discards.is_empty();
+        info.tsumo = true;
+        info.haitei = self.wall.is_haitei() && !self.rinshan;
+        info.rinshan = self.rinshan;
+        info.tenho = first_draw && seat == self.config.dealer;
+        info.chiho = first_draw && seat != self.config.dealer;
+        self.evaluate_agari(info)
+    }
+
+    // ロン和了の判定（chankanが真なら搶槓）
+    fn evaluate_ron(
+        &self,
+        seat: usize,
+        discarder: usize,
+        tile: Tile,
+        chankan: bool,
+    ) -> Option<(AgariInformation, Score)> {
+        let player = &self.players[seat];
+        if seat == discarder
+            || !waiting_tile_ids(&player.hand).contains(&tile.id)
+            || self.is_furiten(seat)
+        {
+            return None;
+        }
+        let mut info = self.agari_information(seat, player.hand.clone(), tile);
+        info.haitei = !chankan && self.wall.is_haitei();
+        info.chankan = chankan;
+        self.evaluate_agari(info)
+    }
+}
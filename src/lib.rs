@@ -1,6 +1,10 @@
+pub mod game;
+pub mod kyoku;
 pub mod score;
+pub mod settlement;
 pub mod shanten;
 pub mod types;
+pub mod wall;
 
 #[macro_use]
 extern crate num_derive;
@@ -1,24 +1,217 @@
 extern crate clap;
-use clap::{AppSettings, Arg, Command};
+use clap::{AppSettings, Arg, ArgMatches, Command};
 use mjtools::score::*;
 use mjtools::shanten::*;
 use mjtools::types::*;
-use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{BufRead, BufReader, Write};
 
-fn main() {
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+// 出力形式（JSONはserde featureが必要。既定のcli featureで有効になる）
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+// JSON出力: 有効牌とその残り枚数
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct EffectiveTileOutput {
+    tile: TileId,
+    count: i32,
+}
+
+// JSON出力: 向聴数計算結果
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct ShantenOutput {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hand"))]
+    hand: Hand,
+    shanten: i32,
+    effective_tiles: Option<Vec<EffectiveTileOutput>>,
+    total_effective_tiles: Option<i32>,
+    waits: Option<Vec<WaitingTile>>,
+}
+
+// JSON出力: 打牌候補
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct DiscardOutput {
+    discard: TileId,
+    shanten: i32,
+    expected_ukeire: Option<f64>,
+    effective_tiles: Vec<EffectiveTileOutput>,
+    total_effective_tiles: i32,
+}
+
+// JSON出力: 打牌候補の評価結果
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct NanikiruOutput {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hand"))]
+    hand: Hand,
+    candidates: Vec<DiscardOutput>,
+}
+
+// JSON出力: 符の内訳の項目
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct FuOutput {
+    name: String,
+    tile: Option<TileId>,
+    fu: i32,
+}
+
+// JSON出力: 成立役
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+struct YakuOutput {
+    yaku: Yaku,
+    name: String,
+    han: Option<i32>,
+    yakuman: Option<i32>,
+}
+
+// JSON出力: 支払い情報
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "lowercase"))]
+enum FeedOutput {
+    Ron { point: i32 },
+    Tsumo { oya: i32, ko: i32 },
+}
+
+// JSON出力: 得点計算結果
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct ScoreOutput {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hand"))]
+    hand: Hand,
+    wining_tile: Tile,
+    han: i32,
+    fu: i32,
+    point: i32,
+    fu_detail: Vec<FuOutput>,
+    yaku: Vec<YakuOutput>,
+    feed: FeedOutput,
+}
+
+// JSON出力: サブコマンドの実行エラー
+#[cfg_attr(feature = "serde", derive(Serialize))]
+struct CommandError {
+    error: String,
+    detail: String,
+}
+
+// JSON出力: サブコマンドの実行結果
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(untagged)]
+enum CommandOutput {
+    Shanten(ShantenOutput),
+    Nanikiru(NanikiruOutput),
+    Score(ScoreOutput),
+}
+
+// JSON出力: 一括処理の1行分の結果
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(untagged)]
+enum BatchResult {
+    Ok(CommandOutput),
+    Err(CommandError),
+}
+
+// JSON出力: 一括処理の行番号付きの結果
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct BatchOutput {
+    line: usize,
+    #[serde(flatten)]
+    result: BatchResult,
+}
+
+impl CommandError {
+    fn new(message: &str, e: impl std::fmt::Display) -> Self {
+        CommandError {
+            error: message.to_string(),
+            detail: e.to_string(),
+        }
+    }
+}
+
+// コマンドライン引数の定義
+fn build_command() -> Command<'static> {
     let command = Command::new("mjtools")
         .version(env!("CARGO_PKG_VERSION"))
         .about("Mahjong analyzing tools")
+        .arg(
+            Arg::new("unicode")
+                .help("Print tiles as Unicode mahjong glyphs")
+                .long("unicode")
+                .global(true),
+        )
+        .arg(
+            Arg::new("format")
+                .help("Specify output format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(if cfg!(feature = "serde") {
+                    &["text", "json"][..]
+                } else {
+                    &["text"][..]
+                })
+                .default_value("text")
+                .global(true),
+        )
         .subcommand(
             Command::new("shanten")
                 .about("Calculate shanten and listup effective tiles")
-                .arg(Arg::new("hand").help("Specify hand tiles formatted as [([1-9]m)([1-9]p)([1-9]s)東南西北白発中]+").required(true)),
+                .setting(AppSettings::DeriveDisplayOrder)
+                .arg(Arg::new("hand").help("Specify hand tiles formatted as mjscore (1m2m3m東) or compact (123m11z[456p](7777s)) notation").required(true))
+                .arg(
+                    Arg::new("omote dora")
+                        .help("Specify omote dora tiles")
+                        .long("omotedora")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("visible")
+                        .help("Specify visible tiles on the table (discards, other players' melds)")
+                        .long("visible")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            Command::new("nanikiru")
+                .about("Rank discards of a hand after drawing")
+                .setting(AppSettings::DeriveDisplayOrder)
+                .arg(Arg::new("hand").help("Specify 14 hand tiles formatted as mjscore (1m2m3m東) or compact (123m11z[456p](7777s)) notation").required(true))
+                .arg(
+                    Arg::new("omote dora")
+                        .help("Specify omote dora tiles")
+                        .long("omotedora")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("visible")
+                        .help("Specify visible tiles on the table (discards, other players' melds)")
+                        .long("visible")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("twostep")
+                        .help("Rank 1 or 2 shanten discards by expected ukeire after the next draw")
+                        .long("twostep"),
+                ),
         )
         .subcommand(
             Command::new("score")
                 .about("Calculate score")
                 .setting(AppSettings::DeriveDisplayOrder)
-                .arg(Arg::new("hand").help("specify hand tiles formatted as [([1-9]m)([1-9]p)([1-9]s)東南西北白発中]+").required(true))
+                .arg(Arg::new("hand").help("specify hand tiles formatted as mjscore (1m2m3m東) or compact (123m11z[456p](7777s)) notation").required(true))
                 .arg(
                     Arg::new("wining tile")
                         .help("Specify wining (tsumo/ron) tile")
@@ -60,10 +253,16 @@ fn main() {
                 )
                 .arg(
                     Arg::new("minkan")
-                        .help("Specify minkan/kakan tiles")
+                        .help("Specify minkan tiles")
                         .long("minkan")
                         .takes_value(true),
                 )
+                .arg(
+                    Arg::new("kakan")
+                        .help("Specify kakan tiles (the last tile of each kan is the added one)")
+                        .long("kakan")
+                        .takes_value(true),
+                )
                 .arg(
                     Arg::new("omote dora")
                         .help("Specify omote dora tiles")
@@ -122,175 +321,291 @@ fn main() {
                     Arg::new("chankan")
                         .help("Specify whether chankan or not")
                         .long("chankan"),
+                )
+                .arg(
+                    Arg::new("rules")
+                        .help("Specify rule preset (default, m-league, tenhou, wrc) or rules file")
+                        .long("rules")
+                        .takes_value(true),
                 ),
         );
+    // 一括処理はJSONで入出力するためserde featureが必要（既定のcli featureで有効になる）
+    #[cfg(feature = "serde")]
+    let command = command.subcommand(
+            Command::new("batch")
+                .about("Run shanten/nanikiru/score requests line by line and print results as JSON lines")
+                .arg(
+                    Arg::new("input")
+                        .help("Specify input file of requests (command line style or JSON), reads stdin if omitted or '-'"),
+                ),
+        );
+    command
+}
 
+fn main() {
     // 引数を解析
-    let matches = command.get_matches();
-
-    // 向聴数計算
-    if let Some(matches) = matches.subcommand_matches("shanten") {
-        // 手牌
-        let hand =
-            Tile::from_mjscorestr(matches.value_of("hand").unwrap()).expect("Faild to parse hand");
-        let idmarged: Vec<TileId> = hand.iter().map(|t| t.id).collect();
+    let matches = build_command().get_matches();
+    let unicode = matches.is_present("unicode");
+    let format = match matches.value_of("format") {
+        #[cfg(feature = "serde")]
+        Some("json") => OutputFormat::Json,
+        _ => OutputFormat::Text,
+    };
+
+    match matches.subcommand() {
         // 向聴数計算
-        let shanten = calculate_shanten(&idmarged);
-        println!(
-            "Shanten: {} {}",
-            shanten,
-            match shanten {
-                -1 => {
-                    "(agari)"
-                }
-                0 => {
-                    "(tenpai)"
-                }
-                _ => "",
+        Some(("shanten", matches)) => {
+            let output = run_shanten(matches).unwrap_or_else(|e| exit_with_error(format, e));
+            match format {
+                #[cfg(feature = "serde")]
+                OutputFormat::Json => print_json(&output),
+                OutputFormat::Text => print_shanten(&output, unicode),
             }
-        );
-        // 有効牌列挙（自摸前に可能）
-        if idmarged.len() == 13 {
-            let effective_tiles =
-                listup_effective_tiles(&idmarged).expect("Faild to listup effective tiles");
-            let effective_tiles_str: Vec<String> = effective_tiles
-                .into_iter()
-                .map(|id| Tile { id, aka: false }.to_mjscorestr())
-                .collect();
-            print!("Effective tiles: ");
-            for s in effective_tiles_str {
-                print!("{}, ", s)
+        }
+        // 打牌候補の評価
+        Some(("nanikiru", matches)) => {
+            let output = run_nanikiru(matches).unwrap_or_else(|e| exit_with_error(format, e));
+            match format {
+                #[cfg(feature = "serde")]
+                OutputFormat::Json => print_json(&output),
+                OutputFormat::Text => print_nanikiru(&output, unicode),
             }
-            println!();
         }
+        // 得点計算
+        Some(("score", matches)) => {
+            let output = run_score(matches).unwrap_or_else(|e| exit_with_error(format, e));
+            match format {
+                #[cfg(feature = "serde")]
+                OutputFormat::Json => print_json(&output),
+                OutputFormat::Text => print_score(&output, unicode),
+            }
+        }
+        // 一括処理
+        #[cfg(feature = "serde")]
+        Some(("batch", matches)) => run_batch(matches, format),
+        _ => {}
     }
+}
 
-    // 得点計算
-    if let Some(matches) = matches.subcommand_matches("score") {
-        let strwindmap = HashMap::from([
-            ("ton", Wind::Ton),
-            ("nan", Wind::Nan),
-            ("sha", Wind::Sha),
-            ("pee", Wind::Pee),
-        ]);
-        let mut melds: Vec<Meld> = vec![];
-        let mut dora = Dora {
-            omote: vec![],
-            ura: vec![],
-        };
-        // 純手牌
-        let hand =
-            Tile::from_mjscorestr(matches.value_of("hand").unwrap()).expect("Faild to parse hand");
-        // 和了牌
-        let get = Tile::from_mjscorestr(matches.value_of("wining tile").unwrap())
-            .expect("Faild to parse wining tile");
-        if get.len() > 1 {
-            panic!("wining tile must be one");
+// 表ドラ表示牌と卓上で見えている牌を読み込み
+fn parse_visible_tiles(matches: &ArgMatches) -> Result<(Dora, Vec<TileId>), CommandError> {
+    // 表ドラ牌
+    let mut dora = Dora {
+        omote: vec![],
+        ura: vec![],
+    };
+    if let Some(d) = matches.value_of("omote dora") {
+        let ts = Tile::from_compactstr(d)
+            .map_err(|e| CommandError::new("Failed to parse omote dora", e))?;
+        dora.omote.extend(&ts);
+    }
+    // 卓上で見えている牌
+    let visible: Vec<TileId> = match matches.value_of("visible") {
+        Some(v) => Tile::from_compactstr(v)
+            .map_err(|e| CommandError::new("Failed to parse visible tiles", e))?
+            .iter()
+            .map(|t| t.id)
+            .collect(),
+        None => vec![],
+    };
+    Ok((dora, visible))
+}
+
+// 向聴数計算を実行
+fn run_shanten(matches: &ArgMatches) -> Result<ShantenOutput, CommandError> {
+    // 手牌
+    let hand = Hand::from_compactstr(matches.value_of("hand").unwrap())
+        .map_err(|e| CommandError::new("Failed to parse hand", e))?;
+    // 向聴数計算（純手牌の枚数から副露数を補う）
+    let shanten = calculate_hand_shanten(&hand)
+        .map_err(|e| CommandError::new("Failed to calculate shanten", e))?;
+    // 有効牌列挙（自摸前に可能）
+    let mut effective_tiles = None;
+    let mut waits = None;
+    if hand.hand.len() + 3 * hand.melds.len() == 13 {
+        let (dora, visible) = parse_visible_tiles(matches)?;
+        effective_tiles = Some(
+            count_effective_tiles(&hand, &dora, &visible)
+                .map_err(|e| CommandError::new("Failed to listup effective tiles", e))?,
+        );
+        // 聴牌していれば待ちの形を列挙
+        if shanten == 0 {
+            waits = Some(
+                listup_waiting_tiles(&hand)
+                    .map_err(|e| CommandError::new("Failed to listup waiting tiles", e))?,
+            );
         }
-        let wining_tile = get[0];
-        // 自風
-        let player = *strwindmap
-            .get(matches.value_of("player wind").unwrap())
-            .expect("Invalid player wind string specified");
-        // 場風
-        let round = *strwindmap
-            .get(matches.value_of("round wind").unwrap())
-            .expect("Invalid round wind string specified");
-        // チー牌
-        if let Some(c) = matches.value_of("chow") {
-            let chow = Tile::from_mjscorestr(c).expect("Faild to parse chow");
-            if chow.len() % 3 != 0 {
-                panic!("invalid number of tiles in chow");
-            }
-            if chow.iter().any(|c| !c.id.is_suhai()) {
-                panic!("chow tiles must include suhai only");
-            }
-            for i in (0..chow.len()).step_by(3) {
-                if (chow[i].id.nth(1) != chow[i + 1].id) || (chow[i].id.nth(2) != chow[i + 2].id) {
-                    panic!("invalid order of tiles in chow");
-                }
-                melds.push(Meld::Chow {
-                    tiles: [chow[i], chow[i + 1], chow[i + 2]],
-                });
-            }
+    }
+    Ok(ShantenOutput {
+        hand,
+        shanten,
+        total_effective_tiles: effective_tiles
+            .as_ref()
+            .map(|ts| ts.iter().map(|(_, n)| n).sum()),
+        effective_tiles: effective_tiles.as_deref().map(effective_tiles_output),
+        waits,
+    })
+}
+
+// 打牌候補の評価を実行
+fn run_nanikiru(matches: &ArgMatches) -> Result<NanikiruOutput, CommandError> {
+    // 手牌
+    let hand = Hand::from_compactstr(matches.value_of("hand").unwrap())
+        .map_err(|e| CommandError::new("Failed to parse hand", e))?;
+    let (dora, visible) = parse_visible_tiles(matches)?;
+    let candidates = analyze_discards(&hand, &dora, &visible)
+        .map_err(|e| CommandError::new("Failed to analyze discards", e))?;
+    // 次の自摸後の受け入れの期待値を計算
+    let mut evaluated: Vec<(DiscardCandidate, Option<f64>)> = vec![];
+    for c in candidates {
+        if !matches.is_present("twostep") || !(1..=2).contains(&c.shanten) {
+            evaluated.push((c, None));
+            continue;
         }
-        // ポン牌
-        if let Some(p) = matches.value_of("pung") {
-            let pung = Tile::from_mjscorestr(p).expect("Faild to parse pung");
-            if pung.len() % 3 != 0 {
-                panic!("invalid number of tiles in pung");
-            }
-            for i in (0..pung.len()).step_by(3) {
-                if (pung[i].id != pung[i + 1].id) || (pung[i].id != pung[i + 2].id) {
-                    panic!("pung tiles are must be same");
-                }
-                melds.push(Meld::Pung {
-                    tiles: [pung[i], pung[i + 1], pung[i + 2]],
-                });
-            }
+        let mut remain = hand.clone();
+        remain
+            .hand
+            .remove(remain.hand.iter().position(|t| t.id == c.discard).unwrap());
+        let mut discarded_visible = visible.clone();
+        discarded_visible.push(c.discard);
+        let evaluation = evaluate_ukeire(&remain, &dora, &discarded_visible)
+            .map_err(|e| CommandError::new("Failed to evaluate ukeire", e))?;
+        evaluated.push((c, Some(evaluation.expected_ukeire)));
+    }
+    // 受け入れが同じ打牌は期待値の高い順に並べる
+    evaluated.sort_by(|(a, ea), (b, eb)| {
+        (a.shanten, -a.num_effective_tiles())
+            .cmp(&(b.shanten, -b.num_effective_tiles()))
+            .then(eb.partial_cmp(ea).unwrap())
+    });
+    Ok(NanikiruOutput {
+        hand,
+        candidates: evaluated
+            .iter()
+            .map(|(c, expected)| DiscardOutput {
+                discard: c.discard,
+                shanten: c.shanten,
+                expected_ukeire: *expected,
+                effective_tiles: effective_tiles_output(&c.effective_tiles),
+                total_effective_tiles: c.num_effective_tiles(),
+            })
+            .collect(),
+    })
+}
+
+// 得点計算を実行
+fn run_score(matches: &ArgMatches) -> Result<ScoreOutput, CommandError> {
+    let mut dora = Dora {
+        omote: vec![],
+        ura: vec![],
+    };
+    // 手牌（簡易表記の副露を含む）
+    let Hand { hand, melds } = Hand::from_compactstr(matches.value_of("hand").unwrap())
+        .map_err(|e| CommandError::new("Failed to parse hand", e))?;
+    // 和了牌
+    let get = Tile::from_compactstr(matches.value_of("wining tile").unwrap())
+        .map_err(|e| CommandError::new("Failed to parse wining tile", e))?;
+    if get.len() != 1 {
+        return Err(CommandError::new(
+            "Failed to parse wining tile",
+            "wining tile must be one",
+        ));
+    }
+    let wining_tile = get[0];
+    // 自風
+    let player: Wind = matches
+        .value_of("player wind")
+        .unwrap()
+        .parse()
+        .map_err(|e| CommandError::new("Invalid player wind string specified", e))?;
+    // 場風
+    let round: Wind = matches
+        .value_of("round wind")
+        .unwrap()
+        .parse()
+        .map_err(|e| CommandError::new("Invalid round wind string specified", e))?;
+    // 副露（オプションの牌を3枚/4枚ずつ区切る）
+    let mut builder = HandBuilder::new().tiles(&hand);
+    for meld in melds {
+        builder = builder.meld(meld);
+    }
+    if let Some(c) = matches.value_of("chow") {
+        let chow =
+            Tile::from_compactstr(c).map_err(|e| CommandError::new("Failed to parse chow", e))?;
+        for tiles in chow.chunks(3) {
+            builder = builder.chow(tiles);
         }
-        // 暗槓牌
-        if let Some(ak) = matches.value_of("ankan") {
-            let ankan = Tile::from_mjscorestr(ak).expect("Faild to parse ankan");
-            if ankan.len() % 4 != 0 {
-                panic!("invalid number of tiles in ankan");
-            }
-            for i in (0..ankan.len()).step_by(4) {
-                if (ankan[i].id != ankan[i + 1].id)
-                    || (ankan[i].id != ankan[i + 2].id)
-                    || (ankan[i].id != ankan[i + 3].id)
-                {
-                    panic!("ankan tiles are must be same");
-                }
-                melds.push(Meld::Ankan {
-                    tiles: [ankan[i], ankan[i + 1], ankan[i + 2], ankan[i + 4]],
-                });
-            }
+    }
+    if let Some(p) = matches.value_of("pung") {
+        let pung =
+            Tile::from_compactstr(p).map_err(|e| CommandError::new("Failed to parse pung", e))?;
+        for tiles in pung.chunks(3) {
+            builder = builder.pung(tiles);
         }
-        // 明槓牌
-        if let Some(mk) = matches.value_of("minkan") {
-            let minkan = Tile::from_mjscorestr(mk).expect("Faild to parse minkan");
-            if minkan.len() % 4 != 0 {
-                panic!("invalid number of tiles in minkan");
-            }
-            for i in (0..minkan.len()).step_by(4) {
-                if (minkan[i].id != minkan[i + 1].id)
-                    || (minkan[i].id != minkan[i + 2].id)
-                    || (minkan[i].id != minkan[i + 3].id)
-                {
-                    panic!("minkan tiles are must be same");
-                }
-                melds.push(Meld::Minkan {
-                    tiles: [minkan[i], minkan[i + 1], minkan[i + 2], minkan[i + 4]],
-                });
-            }
+    }
+    if let Some(ak) = matches.value_of("ankan") {
+        let ankan =
+            Tile::from_compactstr(ak).map_err(|e| CommandError::new("Failed to parse ankan", e))?;
+        for tiles in ankan.chunks(4) {
+            builder = builder.ankan(tiles);
         }
-        // 表ドラ牌
-        if let Some(d) = matches.value_of("omote dora") {
-            let ts = Tile::from_mjscorestr(d).expect("Failed to parse omote dora");
-            dora.omote.extend(&ts);
+    }
+    if let Some(mk) = matches.value_of("minkan") {
+        let minkan = Tile::from_compactstr(mk)
+            .map_err(|e| CommandError::new("Failed to parse minkan", e))?;
+        for tiles in minkan.chunks(4) {
+            builder = builder.minkan(tiles);
         }
-        // 裏ドラ牌
-        if let Some(d) = matches.value_of("ura dora") {
-            let ts = Tile::from_mjscorestr(d).expect("Failed to parse ura dora");
-            dora.ura.extend(&ts);
+    }
+    if let Some(kk) = matches.value_of("kakan") {
+        let kakan =
+            Tile::from_compactstr(kk).map_err(|e| CommandError::new("Failed to parse kakan", e))?;
+        for tiles in kakan.chunks(4) {
+            builder = builder.kakan(tiles);
         }
-        // 本場数
-        let nhonba: i32 = if let Some(n) = matches.value_of("nhonba") {
-            n.parse().expect("Failed to parse integer in nhonba")
-        } else {
-            0
-        };
-        // 供託リーチ棒本数
-        let nriichi: i32 = if let Some(n) = matches.value_of("nriichi") {
-            n.parse().expect("Failed to parse integer in nriichi")
-        } else {
-            0
-        };
-        // 得点計算
-        let score = calculate_score(&AgariInformation {
+    }
+    // 手牌の検証（和了牌を含めて14枚）
+    let hand = builder
+        .wining_tile(wining_tile)
+        .build()
+        .map_err(|e| CommandError::new("Invalid hand", e))?;
+    // 表ドラ牌
+    if let Some(d) = matches.value_of("omote dora") {
+        let ts = Tile::from_compactstr(d)
+            .map_err(|e| CommandError::new("Failed to parse omote dora", e))?;
+        dora.omote.extend(&ts);
+    }
+    // 裏ドラ牌
+    if let Some(d) = matches.value_of("ura dora") {
+        let ts = Tile::from_compactstr(d)
+            .map_err(|e| CommandError::new("Failed to parse ura dora", e))?;
+        dora.ura.extend(&ts);
+    }
+    // 本場数
+    let nhonba: i32 = match matches.value_of("nhonba") {
+        Some(n) => n
+            .parse()
+            .map_err(|e| CommandError::new("Failed to parse integer in nhonba", e))?,
+        None => 0,
+    };
+    // 供託リーチ棒本数
+    let nriichi: i32 = match matches.value_of("nriichi") {
+        Some(n) => n
+            .parse()
+            .map_err(|e| CommandError::new("Failed to parse integer in nriichi", e))?,
+        None => 0,
+    };
+    // 得点計算ルール
+    let rule = match matches.value_of("rules") {
+        Some(r) => load_rule_config(r)
+            .map_err(|e| CommandError::new(&format!("Failed to load rules '{}'", r), e))?,
+        None => ScoreRuleConfig::default(),
+    };
+    // 得点計算
+    let score = calculate_score_with_rules(
+        &AgariInformation {
             wining_tile,
-            hand: Hand { hand, melds },
+            hand: hand.clone(),
             nhonba,
             nriichi,
             round,
@@ -306,26 +621,373 @@ fn main() {
             tenho: false,
             chiho: false,
             dora,
-        })
-        .expect("Failed to calculate socre");
-        // 翻/符/取得点数
-        println!(
-            "{} han {} fu, {} point",
-            score.han, score.fu, score.point.get
-        );
-        // 成立役リスト
-        print!("yaku: ");
-        for y in score.yaku {
-            print!("{}, ", y.to_jpstr());
+        },
+        &rule,
+    )
+    .map_err(|e| CommandError::new("Failed to calculate score", e))?;
+    Ok(ScoreOutput {
+        hand,
+        wining_tile,
+        han: score.han,
+        fu: score.fu,
+        point: score.point.get,
+        fu_detail: score
+            .fu_detail
+            .iter()
+            .map(|(f, n)| FuOutput {
+                name: f.to_jpstr().to_string(),
+                tile: match f {
+                    Fu::YakuhaiAtama { t }
+                    | Fu::Minko { t }
+                    | Fu::Anko { t }
+                    | Fu::Minkan { t }
+                    | Fu::Ankan { t } => Some(*t),
+                    _ => None,
+                },
+                fu: *n,
+            })
+            .collect(),
+        yaku: score
+            .yaku
+            .iter()
+            .map(|(y, v)| YakuOutput {
+                yaku: *y,
+                name: y.to_jpstr().trim().to_string(),
+                han: match v {
+                    YakuValue::Han(n) => Some(*n),
+                    YakuValue::Yakuman(_) => None,
+                },
+                yakuman: match v {
+                    YakuValue::Han(_) => None,
+                    YakuValue::Yakuman(n) => Some(*n),
+                },
+            })
+            .collect(),
+        feed: match score.point.feed {
+            Feed::Duck { point } => FeedOutput::Ron { point },
+            Feed::Tsumo { ko, oya } => FeedOutput::Tsumo { oya, ko },
+        },
+    })
+}
+
+// 一括処理を実行（1行に1つのリクエストを読み込み、1行に1つの結果をJSONで出力）
+// 入力を読めないなど続行できないエラーはformatに従って表示する
+#[cfg(feature = "serde")]
+fn run_batch(matches: &ArgMatches, format: OutputFormat) {
+    let reader: Box<dyn BufRead> = match matches.value_of("input") {
+        None | Some("-") => Box::new(BufReader::new(std::io::stdin())),
+        Some(path) => Box::new(BufReader::new(File::open(path).unwrap_or_else(|e| {
+            exit_with_error(format, CommandError::new("Failed to open input", e))
+        }))),
+    };
+    let mut out = std::io::stdout().lock();
+    // 引数の定義は全ての行で使い回す
+    let mut command = build_command();
+    let mut nerrors = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.unwrap_or_else(|e| {
+            exit_with_error(format, CommandError::new("Failed to read input", e))
+        });
+        let line = line.trim();
+        // 空行とコメント行は読み飛ばす
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = match run_batch_request(&mut command, line) {
+            Ok(output) => BatchResult::Ok(output),
+            Err(e) => {
+                nerrors += 1;
+                BatchResult::Err(e)
+            }
+        };
+        let output = BatchOutput {
+            line: i + 1,
+            result,
+        };
+        writeln!(
+            out,
+            "{}",
+            serde_json::to_string(&output).expect("Failed to serialize output")
+        )
+        .unwrap_or_else(|e| {
+            exit_with_error(format, CommandError::new("Failed to write output", e))
+        });
+    }
+    // 1つでも失敗したリクエストがあれば異常終了
+    if nerrors > 0 {
+        std::process::exit(1);
+    }
+}
+
+// 一括処理の1行分のリクエストを実行
+#[cfg(feature = "serde")]
+fn run_batch_request(command: &mut Command, line: &str) -> Result<CommandOutput, CommandError> {
+    let args = if line.starts_with('{') {
+        json_request_to_args(line)?
+    } else {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    };
+    let matches = command
+        .try_get_matches_from_mut(std::iter::once("mjtools".to_string()).chain(args))
+        .map_err(|e| {
+            // clapのエラーは使い方の表示を除いて1行にまとめる
+            let message = e.to_string();
+            let detail: Vec<&str> = message
+                .lines()
+                .take_while(|l| !l.trim().is_empty())
+                .map(|l| l.trim())
+                .collect();
+            CommandError::new(
+                "Invalid arguments",
+                detail.join(" ").trim_start_matches("error: "),
+            )
+        })?;
+    match matches.subcommand() {
+        Some(("shanten", matches)) => run_shanten(matches).map(CommandOutput::Shanten),
+        Some(("nanikiru", matches)) => run_nanikiru(matches).map(CommandOutput::Nanikiru),
+        Some(("score", matches)) => run_score(matches).map(CommandOutput::Score),
+        _ => Err(CommandError::new(
+            "Invalid arguments",
+            "command must be one of shanten, nanikiru or score",
+        )),
+    }
+}
+
+// JSONのリクエストをコマンドライン引数に変換
+// commandはサブコマンド名、hand/wining_tileは位置引数、それ以外のキーは同名のオプションとして扱う
+// （真偽値はtrueのときのみフラグを指定）
+#[cfg(feature = "serde")]
+fn json_request_to_args(line: &str) -> Result<Vec<String>, CommandError> {
+    let request: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line)
+        .map_err(|e| CommandError::new("Failed to parse JSON request", e))?;
+    const POSITIONALS: [&str; 3] = ["command", "hand", "wining_tile"];
+    let mut args = vec![];
+    for key in POSITIONALS {
+        match request.get(key) {
+            Some(serde_json::Value::String(s)) => args.push(s.clone()),
+            Some(_) => {
+                return Err(CommandError::new(
+                    "Invalid JSON request",
+                    format!("'{}' must be a string", key),
+                ))
+            }
+            None => {}
+        }
+    }
+    for (key, value) in &request {
+        if POSITIONALS.contains(&key.as_str()) {
+            continue;
+        }
+        match value {
+            serde_json::Value::Bool(true) => args.push(format!("--{}", key)),
+            serde_json::Value::Bool(false) | serde_json::Value::Null => {}
+            serde_json::Value::String(s) => args.push(format!("--{}={}", key, s)),
+            serde_json::Value::Number(n) => args.push(format!("--{}={}", key, n)),
+            _ => {
+                return Err(CommandError::new(
+                    "Invalid JSON request",
+                    format!("unsupported value for '{}'", key),
+                ))
+            }
+        }
+    }
+    Ok(args)
+}
+
+// 向聴数計算の結果を表示
+fn print_shanten(output: &ShantenOutput, unicode: bool) {
+    if unicode {
+        println!("Hand: {}", hand_to_string(&output.hand, unicode));
+    }
+    println!(
+        "Shanten: {} {}",
+        output.shanten,
+        match output.shanten {
+            -1 => {
+                "(agari)"
+            }
+            0 => {
+                "(tenpai)"
+            }
+            _ => "",
+        }
+    );
+    if let Some(effective_tiles) = &output.effective_tiles {
+        print!("Effective tiles: ");
+        print_effective_tiles(effective_tiles, unicode);
+    }
+    // 聴牌していれば待ちの形を表示
+    if let Some(waits) = &output.waits {
+        print!("Waits: ");
+        for wt in waits {
+            let shapes: Vec<&str> = wt
+                .waits
+                .iter()
+                .map(|w| w.to_jpstr())
+                .chain(wt.patterns.iter().map(|p| p.to_jpstr()))
+                .collect();
+            print!(
+                "{}({}), ",
+                tile_to_string(wt.tile, unicode),
+                shapes.join(",")
+            );
         }
-        // 支払い情報
         println!();
+    }
+}
+
+// 打牌候補の評価結果を表示
+fn print_nanikiru(output: &NanikiruOutput, unicode: bool) {
+    if unicode {
+        println!("Hand: {}", hand_to_string(&output.hand, unicode));
+    }
+    for c in &output.candidates {
+        print!(
+            "discard {}: shanten {}, ",
+            tile_to_string(c.discard, unicode),
+            c.shanten
+        );
+        if let Some(e) = c.expected_ukeire {
+            print!("expected ukeire {:.2}, ", e);
+        }
+        print!("effective tiles: ");
+        print_effective_tiles(&c.effective_tiles, unicode);
+    }
+}
+
+// 得点計算の結果を表示
+fn print_score(output: &ScoreOutput, unicode: bool) {
+    if unicode {
         println!(
-            "feed: {}",
-            match score.point.feed {
-                Feed::Duck { point } => format!("{}", point),
-                Feed::Tsumo { ko, oya } => format!("oya:{} ko:{}", oya, ko),
-            }
+            "Hand: {} {}",
+            hand_to_string(&output.hand, unicode),
+            tile_to_string(output.wining_tile.id, unicode)
         );
     }
+    // 翻/符/取得点数
+    println!(
+        "{} han {} fu, {} point",
+        output.han, output.fu, output.point
+    );
+    // 符の内訳
+    print!("fu: ");
+    for f in &output.fu_detail {
+        match f.tile {
+            Some(t) => print!("{}({}) {}, ", f.name, tile_to_string(t, unicode), f.fu),
+            None => print!("{} {}, ", f.name, f.fu),
+        }
+    }
+    println!();
+    // 成立役リスト
+    print!("yaku: ");
+    for y in &output.yaku {
+        match (y.han, y.yakuman) {
+            (Some(n), _) => print!("{} {} han, ", y.yaku.to_jpstr(), n),
+            (_, Some(1)) => print!("{} yakuman, ", y.yaku.to_jpstr()),
+            (_, Some(n)) => print!("{} {}x yakuman, ", y.yaku.to_jpstr(), n),
+            (None, None) => {}
+        }
+    }
+    // 支払い情報
+    println!();
+    println!(
+        "feed: {}",
+        match output.feed {
+            FeedOutput::Ron { point } => format!("{}", point),
+            FeedOutput::Tsumo { oya, ko } => format!("oya:{} ko:{}", oya, ko),
+        }
+    );
+}
+
+// 有効牌とその合計枚数を表示
+fn print_effective_tiles(effective_tiles: &[EffectiveTileOutput], unicode: bool) {
+    for et in effective_tiles {
+        print!("{}({}), ", tile_to_string(et.tile, unicode), et.count)
+    }
+    println!(
+        "total {} tiles ({} kinds)",
+        effective_tiles.iter().map(|et| et.count).sum::<i32>(),
+        effective_tiles.len()
+    );
+}
+
+// プリセット名またはルールファイルから得点計算ルールを読み込み
+fn load_rule_config(name: &str) -> Result<ScoreRuleConfig, Error> {
+    if ScoreRuleConfig::PRESETS.contains(&name.to_ascii_lowercase().as_str()) {
+        return ScoreRuleConfig::from_preset(name);
+    }
+    let rulestr = std::fs::read_to_string(name).map_err(|e| {
+        Error::from(format!(
+            "{} (neither a rules file nor one of presets: {})",
+            e,
+            ScoreRuleConfig::PRESETS.join(", ")
+        ))
+    })?;
+    ScoreRuleConfig::from_rulestr(&rulestr)
+}
+
+// 牌を表示用の文字列に変換
+fn tile_to_string(id: TileId, unicode: bool) -> String {
+    if unicode {
+        id.to_char().to_string()
+    } else {
+        Tile { id, aka: false }.to_mjscorestr()
+    }
+}
+
+// 手牌を表示用の文字列に変換（副露は空白で区切る）
+fn hand_to_string(hand: &Hand, unicode: bool) -> String {
+    let mut s: String = hand
+        .hand
+        .iter()
+        .map(|t| tile_to_string(t.id, unicode))
+        .collect();
+    for m in &hand.melds {
+        s.push(' ');
+        s += &m
+            .tiles()
+            .iter()
+            .map(|t| tile_to_string(t.id, unicode))
+            .collect::<String>();
+    }
+    s
+}
+
+// 有効牌をJSON出力用に変換
+fn effective_tiles_output(effective_tiles: &[(TileId, i32)]) -> Vec<EffectiveTileOutput> {
+    effective_tiles
+        .iter()
+        .map(|(tile, count)| EffectiveTileOutput {
+            tile: *tile,
+            count: *count,
+        })
+        .collect()
+}
+
+// 手牌を簡易表記の文字列としてシリアライズ
+#[cfg(feature = "serde")]
+fn serialize_hand<S: Serializer>(hand: &Hand, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(hand)
+}
+
+// JSON形式で標準出力に表示
+#[cfg(feature = "serde")]
+fn print_json(value: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("Failed to serialize output")
+    );
+}
+
+// エラーを標準エラー出力に表示して終了（JSON出力時はエラーオブジェクトを表示）
+fn exit_with_error(format: OutputFormat, e: CommandError) -> ! {
+    match format {
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => eprintln!(
+            "{}",
+            serde_json::to_string(&e).expect("Failed to serialize error")
+        ),
+        OutputFormat::Text => eprintln!("{}: {}", e.error, e.detail),
+    }
+    std::process::exit(1);
 }
@@ -1,13 +1,14 @@
 use crate::shanten::*;
 use crate::types::*;
 use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::RwLock;
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
 
-/// 役の識別
-#[derive(Debug, PartialEq, Eq, Hash)]
+/// 役の識別（宣言順が役の表示順）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Yaku {
     /// 立直（リーチ）
     Riichi,
@@ -101,11 +102,63 @@ pub enum Yaku {
     Sukantsu,
     /// ドラ
     Dora,
+    /// 赤ドラ
+    Akadora,
+    /// 裏ドラ
+    Uradora,
     /// 流し満貫
     Nagashimangan,
 }
 
+/// 役の翻数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YakuValue {
+    /// 翻数
+    Han(i32),
+    /// 役満（倍数）
+    Yakuman(i32),
+}
+
+/// 符の内訳の項目
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fu {
+    /// 副底
+    Futei,
+    /// 門前加符
+    Menzen,
+    /// 自摸符
+    Tsumo,
+    /// 単騎待ち
+    Tanki,
+    /// 嵌張待ち
+    Kanchan,
+    /// 辺張待ち
+    Penchan,
+    /// 役牌の雀頭（連風牌は4符）
+    YakuhaiAtama { t: TileId },
+    /// 明刻
+    Minko { t: TileId },
+    /// 暗刻
+    Anko { t: TileId },
+    /// 明槓
+    Minkan { t: TileId },
+    /// 暗槓
+    Ankan { t: TileId },
+    /// 平和（待ち/自摸符はつかない）
+    Pinfu,
+    /// 喰い平和形（副底のみのとき30符に）
+    KuiPinfu,
+    /// 七対子（25符固定）
+    Chitoitsu,
+    /// 1の位の切り上げ
+    Roundup,
+}
+
 /// 和了時の状況
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AgariInformation {
     /// 和了牌
     pub wining_tile: Tile,
@@ -133,7 +186,7 @@ pub struct AgariInformation {
     pub rinshan: bool,
     /// 搶槓？
     pub chankan: bool,
-    /// 流し満貫？
+    /// 流し満貫？（河からの判定はsettlement::is_nagashimanganを使う）
     pub nagashimangan: bool,
     /// 天和？
     pub tenho: bool,
@@ -144,7 +197,8 @@ pub struct AgariInformation {
 }
 
 /// 支払い情報
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Feed {
     /// 放銃時に支払う点数
     Duck { point: i32 },
@@ -153,7 +207,8 @@ pub enum Feed {
 }
 
 /// 得点情報
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// 加点
     pub get: i32,
@@ -162,20 +217,25 @@ pub struct Point {
 }
 
 /// 得点計算結果
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Score {
     /// 翻
     pub han: i32,
     /// 符
     pub fu: i32,
-    /// 成立した役
-    pub yaku: HashSet<Yaku>,
+    /// 符の内訳（合計がfuに一致）
+    pub fu_detail: Vec<(Fu, i32)>,
+    /// 成立した役と翻数（役の宣言順）
+    pub yaku: Vec<(Yaku, YakuValue)>,
     /// 得点情報
     pub point: Point,
 }
 
 /// 得点計算ルール
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ScoreRuleConfig {
     /// 喰いタンあり？
     pub kuitan: bool,
@@ -191,28 +251,72 @@ pub struct ScoreRuleConfig {
     pub nagashimangan: bool,
 }
 
-// 面子の情報
-#[derive(Clone)]
-enum Mentsu {
+/// 面子の情報
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mentsu {
+    /// 明刻（ポン、またはロンで完成した刻子）
     Pung { t: TileId },
+    /// チー
     Chow { min: TileId },
+    /// 暗槓
     Ankan { t: TileId },
+    /// 明槓（大明槓/加槓）
     Minkan { t: TileId },
+    /// 暗刻
     Anko { t: TileId },
+    /// 門前の順子
     Syuntsu { min: TileId },
 }
 
-// 面子が切り分けられた手牌
-struct DividedHand {
-    atama: TileId,
-    mentsu: Vec<Mentsu>,
+/// 面子が切り分けられた手牌
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DividedHand {
+    /// 雀頭
+    pub atama: TileId,
+    /// 面子（副露を先頭に並べる）
+    pub mentsu: Vec<Mentsu>,
+    /// 和了牌の待ちの形
+    pub wait: Wait,
+}
+
+/// 和了形の解釈とその得点
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AgariInterpretation {
+    /// 面子の切り分け
+    pub hand: DividedHand,
+    /// 翻
+    pub han: i32,
+    /// 符
+    pub fu: i32,
+    /// 符の内訳
+    pub fu_detail: Vec<(Fu, i32)>,
+    /// 成立した役と翻数（役の宣言順）
+    pub yaku: Vec<(Yaku, YakuValue)>,
+    /// 高点法により得点計算に採用されたか？
+    pub selected: bool,
+}
+
+/// 待ち牌と、その牌で和了したときに取りうる待ちの形
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaitingTile {
+    /// 待ち牌
+    pub tile: TileId,
+    /// 待ちの形（面子の切り分けにより複数取りうる）
+    pub waits: Vec<Wait>,
+    /// 待ち牌が含まれる複合形
+    pub patterns: Vec<WaitPattern>,
 }
 
 // 得点計算の中間結果
 struct ScoreTriple {
     han: i32,
     fu: i32,
-    yaku: HashSet<Yaku>,
+    fu_detail: Vec<(Fu, i32)>,
+    yaku: Vec<(Yaku, YakuValue)>,
 }
 
 // 役満の翻数
@@ -226,14 +330,7 @@ const HAN_7YAKUMAN: i32 = 91;
 
 lazy_static! {
     // ルール設定
-    static ref RULE_CONFIG: RwLock<ScoreRuleConfig> = RwLock::new(ScoreRuleConfig {
-        kuitan: true,
-        kokushi13_as_double: true,
-        suankotanki_as_double: true,
-        ba1500: false,
-        mangan_roundup: false,
-        nagashimangan: true,
-    });
+    static ref RULE_CONFIG: RwLock<ScoreRuleConfig> = RwLock::new(ScoreRuleConfig::default());
 
     // ドラ対応テーブル
     static ref DORATABLE: HashMap<TileId, TileId> = HashMap::from([
@@ -274,6 +371,193 @@ lazy_static! {
     ]);
 }
 
+impl Default for ScoreRuleConfig {
+    fn default() -> Self {
+        ScoreRuleConfig {
+            kuitan: true,
+            kokushi13_as_double: true,
+            suankotanki_as_double: true,
+            ba1500: false,
+            mangan_roundup: false,
+            nagashimangan: true,
+        }
+    }
+}
+
+impl ScoreRuleConfig {
+    /// プリセット名の一覧
+    pub const PRESETS: [&'static str; 4] = ["default", "m-league", "tenhou", "wrc"];
+
+    /// 名前付きプリセットからルールを取得
+    pub fn from_preset(name: &str) -> Result<Self, Error> {
+        let config = match name.to_ascii_lowercase().as_str() {
+            "default" => ScoreRuleConfig::default(),
+            "m-league" | "mleague" => ScoreRuleConfig {
+                kuitan: true,
+                kokushi13_as_double: false,
+                suankotanki_as_double: false,
+                ba1500: false,
+                mangan_roundup: true,
+                nagashimangan: true,
+            },
+            "tenhou" => ScoreRuleConfig {
+                kuitan: true,
+                kokushi13_as_double: false,
+                suankotanki_as_double: false,
+                ba1500: false,
+                mangan_roundup: false,
+                nagashimangan: true,
+            },
+            "wrc" => ScoreRuleConfig {
+                kuitan: true,
+                kokushi13_as_double: false,
+                suankotanki_as_double: false,
+                ba1500: false,
+                mangan_roundup: true,
+                nagashimangan: false,
+            },
+            _ => {
+                return Err(Error::from(format!(
+                    "Unknown rule preset '{}' (expected one of: {})",
+                    name,
+                    ScoreRuleConfig::PRESETS.join(", ")
+                )));
+            }
+        };
+        Ok(config)
+    }
+
+    /// ルールファイルの内容からルールを読み込み
+    ///
+    /// 1行に1つ`key = value`の形式で記述する（TOMLのサブセット）。
+    /// `"`で囲まれていない`#`以降はコメント。`preset = "名前"`を指定するとそのプリセットを基に
+    /// 残りの設定で上書きする。
+    ///
+    /// ```text
+    /// # 天鳳ルールから満貫切り上げのみ変更
+    /// preset = "tenhou"
+    /// mangan_roundup = true
+    /// ```
+    pub fn from_rulestr(s: &str) -> Result<Self, Error> {
+        let mut preset: Option<String> = None;
+        let mut entries: Vec<(&str, bool)> = vec![];
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            // コメントと空行を除外（"で囲まれた中の#はコメントではない）
+            let mut quoted = false;
+            let comment = line.char_indices().find(|(_, c)| {
+                if *c == '"' {
+                    quoted = !quoted;
+                }
+                *c == '#' && !quoted
+            });
+            let line = match comment {
+                Some((pos, _)) => &line[..pos],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((k, v)) => (k.trim(), v.trim()),
+                None => {
+                    return Err(Error::from(format!(
+                        "line {}: expected 'key = value' but got '{}'",
+                        lineno, line
+                    )));
+                }
+            };
+            // 重複指定は誤りとする
+            if entries.iter().any(|(k, _)| *k == key) || (key == "preset" && preset.is_some()) {
+                return Err(Error::from(format!(
+                    "line {}: duplicate rule key '{}'",
+                    lineno, key
+                )));
+            }
+            if key == "preset" {
+                let name = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .ok_or_else(|| {
+                        Error::from(format!(
+                            "line {}: preset name must be quoted: {}",
+                            lineno, value
+                        ))
+                    })?;
+                preset = Some(name.to_string());
+                continue;
+            }
+            // 値より先にキーを確認
+            if rule_flag(&mut ScoreRuleConfig::default(), key).is_none() {
+                return Err(Error::from(format!(
+                    "line {}: unknown rule key '{}'",
+                    lineno, key
+                )));
+            }
+            let flag = match value {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(Error::from(format!(
+                        "line {}: value of '{}' must be true or false: {}",
+                        lineno, key, value
+                    )));
+                }
+            };
+            entries.push((key, flag));
+        }
+
+        // プリセットを基に上書き
+        let mut config = match preset {
+            Some(name) => ScoreRuleConfig::from_preset(&name)?,
+            None => ScoreRuleConfig::default(),
+        };
+        for (key, flag) in entries {
+            *rule_flag(&mut config, key).unwrap() = flag;
+        }
+
+        Ok(config)
+    }
+}
+
+// ルールファイルのキーに対応する設定項目
+fn rule_flag<'a>(config: &'a mut ScoreRuleConfig, key: &str) -> Option<&'a mut bool> {
+    match key {
+        "kuitan" => Some(&mut config.kuitan),
+        "kokushi13_as_double" => Some(&mut config.kokushi13_as_double),
+        "suankotanki_as_double" => Some(&mut config.suankotanki_as_double),
+        "ba1500" => Some(&mut config.ba1500),
+        "mangan_roundup" => Some(&mut config.mangan_roundup),
+        "nagashimangan" => Some(&mut config.nagashimangan),
+        _ => None,
+    }
+}
+
+impl Fu {
+    /// 日本語名に変換
+    pub fn to_jpstr(&self) -> &str {
+        match self {
+            Fu::Futei => "副底",
+            Fu::Menzen => "門前加符",
+            Fu::Tsumo => "自摸符",
+            Fu::Tanki => "単騎待ち",
+            Fu::Kanchan => "嵌張待ち",
+            Fu::Penchan => "辺張待ち",
+            Fu::YakuhaiAtama { .. } => "役牌雀頭",
+            Fu::Minko { .. } => "明刻",
+            Fu::Anko { .. } => "暗刻",
+            Fu::Minkan { .. } => "明槓",
+            Fu::Ankan { .. } => "暗槓",
+            Fu::Pinfu => "平和",
+            Fu::KuiPinfu => "喰い平和",
+            Fu::Chitoitsu => "七対子",
+            Fu::Roundup => "切り上げ",
+        }
+    }
+}
+
 impl Yaku {
     /// 日本語名に変換
     pub fn to_jpstr(&self) -> &str {
@@ -324,6 +608,8 @@ impl Yaku {
             Yaku::Ryuiso => "緑一色",
             Yaku::Sukantsu => "四槓子",
             Yaku::Dora => "ドラ",
+            Yaku::Akadora => "赤ドラ",
+            Yaku::Uradora => "裏ドラ",
             Yaku::Nagashimangan => "流し満貫",
         }
     }
@@ -334,25 +620,25 @@ fn roundup(val: i32, n: i32) -> i32 {
     ((val + n - 1) / n) * n
 }
 
-// ドラ数の数え上げ
-fn count_num_doras(info: &AgariInformation) -> i32 {
-    let update_num_dora = |t: Tile, num_dora: &mut i32| {
+// ドラ数の数え上げ（表ドラ, 赤ドラ, 裏ドラの順）
+fn count_num_doras(info: &AgariInformation) -> (i32, i32, i32) {
+    let update_num_dora = |t: Tile, num_dora: &mut (i32, i32, i32)| {
         if t.aka {
-            *num_dora += 1;
+            num_dora.1 += 1;
         }
         for d in &info.dora.omote {
             if t.id == *DORATABLE.get(&d.id).unwrap() {
-                *num_dora += 1;
+                num_dora.0 += 1;
             }
         }
         for d in &info.dora.ura {
             if t.id == *DORATABLE.get(&d.id).unwrap() {
-                *num_dora += 1;
+                num_dora.2 += 1;
             }
         }
     };
 
-    let mut ndoras: i32 = 0;
+    let mut ndoras = (0, 0, 0);
 
     // 和了牌
     update_num_dora(info.wining_tile, &mut ndoras);
@@ -361,12 +647,12 @@ fn count_num_doras(info: &AgariInformation) -> i32 {
     }
     for m in &info.hand.melds {
         match m {
-            Meld::Pung { tiles } | Meld::Chow { tiles } => {
+            Meld::Pung { tiles, .. } | Meld::Chow { tiles, .. } => {
                 for t in tiles {
                     update_num_dora(*t, &mut ndoras);
                 }
             }
-            Meld::Ankan { tiles } | Meld::Minkan { tiles } | Meld::Kakan { tiles } => {
+            Meld::Ankan { tiles } | Meld::Minkan { tiles, .. } | Meld::Kakan { tiles, .. } => {
                 for t in tiles {
                     update_num_dora(*t, &mut ndoras);
                 }
@@ -377,6 +663,26 @@ fn count_num_doras(info: &AgariInformation) -> i32 {
     ndoras
 }
 
+// ドラの役/翻計算
+fn calculate_dora_yaku_han(info: &AgariInformation) -> (Vec<(Yaku, YakuValue)>, i32) {
+    let mut yaku = vec![];
+    let mut han = 0;
+
+    let (nomote, naka, nura) = count_num_doras(info);
+    for (y, n) in [
+        (Yaku::Dora, nomote),
+        (Yaku::Akadora, naka),
+        (Yaku::Uradora, nura),
+    ] {
+        if n > 0 {
+            yaku.push((y, YakuValue::Han(n)));
+            han += n;
+        }
+    }
+
+    (yaku, han)
+}
+
 /// 得点計算ルールの取得
 pub fn get_rule_config() -> ScoreRuleConfig {
     *RULE_CONFIG.read().unwrap()
@@ -388,8 +694,16 @@ pub fn set_rule_config(config: &ScoreRuleConfig) {
     *current = *config;
 }
 
-/// 得点計算
+/// 得点計算（set_rule_configで設定したルールを使用）
 pub fn calculate_score(info: &AgariInformation) -> Result<Score, Error> {
+    calculate_score_with_rules(info, &get_rule_config())
+}
+
+/// 指定したルールによる得点計算
+pub fn calculate_score_with_rules(
+    info: &AgariInformation,
+    rule: &ScoreRuleConfig,
+) -> Result<Score, Error> {
     // 面前でないのに立直
     if !info.hand.is_menzen() && (info.riichi || info.doubleriichi) {
         return Err(Error::from("Invalid agari: melded but also riichied."));
@@ -408,12 +722,13 @@ pub fn calculate_score(info: &AgariInformation) -> Result<Score, Error> {
     }
 
     // 流し満貫判定
-    if RULE_CONFIG.read().unwrap().nagashimangan && info.nagashimangan {
+    if rule.nagashimangan && info.nagashimangan {
         let score = Score {
             han: 5,
             fu: 0,
-            yaku: HashSet::from([Yaku::Nagashimangan]),
-            point: calculate_point(info, 5, 0),
+            fu_detail: vec![],
+            yaku: vec![(Yaku::Nagashimangan, YakuValue::Han(5))],
+            point: calculate_point(info, rule, 5, 0),
         };
         return Ok(score);
     }
@@ -433,23 +748,30 @@ pub fn calculate_score(info: &AgariInformation) -> Result<Score, Error> {
 
     // 国士無双の翻/複合役計算
     if calculate_kokushimusou_shanten(marged.as_slice()) == -1 {
-        return Ok(calculate_kokushimusou_score(info, &marged_counts));
+        return Ok(calculate_kokushimusou_score(info, rule, &marged_counts));
     }
 
     // 七対子の翻/複合役計算
-    if calculate_chitoitsu_shanten(marged.as_slice()) == -1 {
-        return Ok(calculate_chitoitsu_score(info, &marged_counts));
-    }
+    let chitoitsu_score = if calculate_chitoitsu_shanten(marged.as_slice()) == -1 {
+        let score = calculate_chitoitsu_score(info, rule, &marged_counts);
+        // 二盃口の形でなければ七対子で確定
+        if calculate_normal_shanten(marged.as_slice()) != -1 {
+            return Ok(score);
+        }
+        Some(score)
+    } else {
+        None
+    };
 
     // 国士無双以外の役満の翻/複合役計算
-    if let Some(score) = calculate_yakuman_score(info, &marged_counts) {
+    if let Some(score) = calculate_yakuman_score(info, rule, &marged_counts) {
         return Ok(score);
     }
 
     // 切り分けが必要な役の判定
     let mut score_triple = calculate_dividedhand_score(info);
     // 切り分けが不要な役の判定
-    let (marged_yaku, marged_han) = calculate_margedand_yaku_han(info, &marged_counts);
+    let (marged_yaku, marged_han) = calculate_margedand_yaku_han(info, rule, &marged_counts);
 
     // 結果をマージ
     score_triple.han += marged_han;
@@ -457,23 +779,31 @@ pub fn calculate_score(info: &AgariInformation) -> Result<Score, Error> {
 
     // 役がついてない
     if score_triple.han == 0 {
-        return Err(Error::from("Invalid agari: There are no yaku"));
+        return chitoitsu_score.ok_or_else(|| Error::from("Invalid agari: There are no yaku"));
     }
 
-    Ok(Score {
+    // 役を宣言順に並べる
+    score_triple.yaku.sort_by_key(|(y, _)| *y);
+
+    let score = Score {
         han: score_triple.han,
         fu: score_triple.fu,
+        fu_detail: score_triple.fu_detail,
         yaku: score_triple.yaku,
-        point: calculate_point(info, score_triple.han, score_triple.fu),
-    })
+        point: calculate_point(info, rule, score_triple.han, score_triple.fu),
+    };
+
+    // 二盃口の形は七対子と比べて高い方を採用（高点法）
+    match chitoitsu_score {
+        Some(chitoitsu) if chitoitsu.point.get > score.point.get => Ok(chitoitsu),
+        _ => Ok(score),
+    }
 }
 
 // 基本点計算
-fn calculate_basic_point(han: i32, fu: i32) -> i32 {
+fn calculate_basic_point(rule: &ScoreRuleConfig, han: i32, fu: i32) -> i32 {
     // 満貫切り上げ
-    if RULE_CONFIG.read().unwrap().mangan_roundup
-        && ((han == 4 && fu == 30) || (han == 3 && fu == 60))
-    {
+    if rule.mangan_roundup && ((han == 4 && fu == 30) || (han == 3 && fu == 60)) {
         return 2000;
     }
 
@@ -506,11 +836,11 @@ fn calculate_basic_point(han: i32, fu: i32) -> i32 {
 }
 
 // 得点計算
-fn calculate_point(info: &AgariInformation, han: i32, fu: i32) -> Point {
+fn calculate_point(info: &AgariInformation, rule: &ScoreRuleConfig, han: i32, fu: i32) -> Point {
     // 基本点計算
-    let basic_point = calculate_basic_point(han, fu);
+    let basic_point = calculate_basic_point(rule, han, fu);
     // 積み棒得点
-    let tsumibo_point = if RULE_CONFIG.read().unwrap().ba1500 {
+    let tsumibo_point = if rule.ba1500 {
         1500 * info.nhonba
     } else {
         300 * info.nhonba
@@ -564,215 +894,239 @@ fn calculate_point(info: &AgariInformation, han: i32, fu: i32) -> Point {
 }
 
 // 国士無双の翻/複合役計算
-fn calculate_kokushimusou_score(info: &AgariInformation, counts: &TileCount) -> Score {
+fn calculate_kokushimusou_score(
+    info: &AgariInformation,
+    rule: &ScoreRuleConfig,
+    counts: &TileCount,
+) -> Score {
     let mut han = 0;
-    let mut yaku: HashSet<Yaku> = HashSet::new();
+    let mut yaku: Vec<(Yaku, YakuValue)> = vec![];
 
     // 天和/地和判定
     if info.tenho {
-        yaku.insert(Yaku::Tenho);
+        yaku.push((Yaku::Tenho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     } else if info.chiho {
-        yaku.insert(Yaku::Chiho);
+        yaku.push((Yaku::Chiho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
 
     // 13面待ちか否か
     if is_kokushimusou13(info, counts) {
-        yaku.insert(Yaku::Kokushimusou13);
-        han += HAN_YAKUMAN;
-        if RULE_CONFIG.read().unwrap().kokushi13_as_double {
+        if rule.kokushi13_as_double {
+            yaku.push((Yaku::Kokushimusou13, YakuValue::Yakuman(2)));
+            han += HAN_2YAKUMAN;
+        } else {
+            yaku.push((Yaku::Kokushimusou13, YakuValue::Yakuman(1)));
             han += HAN_YAKUMAN;
         }
     } else {
-        yaku.insert(Yaku::Kokushimusou);
+        yaku.push((Yaku::Kokushimusou, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
 
     Score {
         han,
         fu: 0,
+        fu_detail: vec![],
         yaku,
-        point: calculate_point(info, han, 0),
+        point: calculate_point(info, rule, han, 0),
     }
 }
 
 // 七対子の翻/複合役計算
-fn calculate_chitoitsu_score(info: &AgariInformation, counts: &TileCount) -> Score {
+fn calculate_chitoitsu_score(
+    info: &AgariInformation,
+    rule: &ScoreRuleConfig,
+    counts: &TileCount,
+) -> Score {
     let mut han = 0;
-    let mut yaku: HashSet<Yaku> = HashSet::new();
+    let mut yaku: Vec<(Yaku, YakuValue)> = vec![];
 
     // 天和/地和判定
     if info.tenho {
-        yaku.insert(Yaku::Tenho);
+        yaku.push((Yaku::Tenho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     } else if info.chiho {
-        yaku.insert(Yaku::Chiho);
+        yaku.push((Yaku::Chiho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 字一色
     if is_tsuiso(info, counts) {
-        yaku.insert(Yaku::Tsuiso);
+        yaku.push((Yaku::Tsuiso, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 役満成立時は終わり
     if han >= HAN_YAKUMAN {
+        yaku.sort_by_key(|(y, _)| *y);
         return Score {
             han,
             fu: 0,
+            fu_detail: vec![],
             yaku,
-            point: calculate_point(info, han, 0),
+            point: calculate_point(info, rule, han, 0),
         };
     }
 
     // 七対子の基本翻/符をセット
-    yaku.insert(Yaku::Chitoitsu);
+    yaku.push((Yaku::Chitoitsu, YakuValue::Han(2)));
     han = 2;
     let fu = 25;
+    let fu_detail = vec![(Fu::Chitoitsu, fu)];
 
     // 自摸
     if info.tsumo {
-        yaku.insert(Yaku::Tsumo);
+        yaku.push((Yaku::Tsumo, YakuValue::Han(1)));
         han += 1;
     }
     // 立直/ダブルリーチ
     if info.doubleriichi {
-        yaku.insert(Yaku::Doubleriichi);
+        yaku.push((Yaku::Doubleriichi, YakuValue::Han(2)));
         han += 2;
     } else if info.riichi {
-        yaku.insert(Yaku::Riichi);
+        yaku.push((Yaku::Riichi, YakuValue::Han(1)));
         han += 1;
     }
     // 一発
     if info.riichi && info.ippatsu {
-        yaku.insert(Yaku::Ippatsu);
+        yaku.push((Yaku::Ippatsu, YakuValue::Han(1)));
         han += 1;
     }
     // 海底摸月/河底撈魚
     if info.haitei {
         if info.tsumo {
-            yaku.insert(Yaku::Haiteitsumo);
+            yaku.push((Yaku::Haiteitsumo, YakuValue::Han(1)));
         } else {
-            yaku.insert(Yaku::Houteiron);
+            yaku.push((Yaku::Houteiron, YakuValue::Han(1)));
         }
         han += 1;
     }
     // ドラ
-    let ndora = count_num_doras(info);
-    if ndora > 0 {
-        yaku.insert(Yaku::Dora);
-        han += ndora;
-    }
+    let (dora_yaku, dora_han) = calculate_dora_yaku_han(info);
+    yaku.extend(dora_yaku);
+    han += dora_han;
     // 混老頭
     if is_honrouto(info, counts) {
-        yaku.insert(Yaku::Honrouto);
+        yaku.push((Yaku::Honrouto, YakuValue::Han(2)));
         han += 2;
     }
     // 清一色
     if is_chinitsu(info, counts) {
-        yaku.insert(Yaku::Chinitsu);
+        yaku.push((Yaku::Chinitsu, YakuValue::Han(6)));
         han += 6;
     }
     // 混一色
     if is_honitsu(info, counts) {
-        yaku.insert(Yaku::Honitsu);
+        yaku.push((Yaku::Honitsu, YakuValue::Han(3)));
         han += 3;
     }
     // 断么九
-    if is_tanyao(info, counts) {
-        yaku.insert(Yaku::Tanyao);
+    if is_tanyao(info, rule, counts) {
+        yaku.push((Yaku::Tanyao, YakuValue::Han(1)));
         han += 1;
     }
 
+    // 役を宣言順に並べる
+    yaku.sort_by_key(|(y, _)| *y);
+
     Score {
         han,
         fu,
+        fu_detail,
         yaku,
-        point: calculate_point(info, han, fu),
+        point: calculate_point(info, rule, han, fu),
     }
 }
 
 // 国士無双以外の翻/複合役計算
-fn calculate_yakuman_score(info: &AgariInformation, counts: &TileCount) -> Option<Score> {
-    let mut yaku: HashSet<Yaku> = HashSet::new();
+fn calculate_yakuman_score(
+    info: &AgariInformation,
+    rule: &ScoreRuleConfig,
+    counts: &TileCount,
+) -> Option<Score> {
+    let mut yaku: Vec<(Yaku, YakuValue)> = vec![];
     let mut han = 0;
 
     // 天和
     if info.tenho {
-        yaku.insert(Yaku::Tenho);
+        yaku.push((Yaku::Tenho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 地和
     if info.chiho {
-        yaku.insert(Yaku::Chiho);
+        yaku.push((Yaku::Chiho, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 九蓮宝燈
     if is_churenpouton(info, counts) {
-        yaku.insert(Yaku::Churenpouton);
+        yaku.push((Yaku::Churenpouton, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 九蓮宝燈9面待ち
     if is_churenpouton9(info, counts) {
-        yaku.insert(Yaku::Churenpouton9);
+        yaku.push((Yaku::Churenpouton9, YakuValue::Yakuman(2)));
         han += HAN_2YAKUMAN;
     }
     // 四暗刻単騎待ち
     if is_suankotanki(info, counts) {
-        yaku.insert(Yaku::Suankotanki);
-        han += HAN_YAKUMAN;
-        if RULE_CONFIG.read().unwrap().suankotanki_as_double {
+        if rule.suankotanki_as_double {
+            yaku.push((Yaku::Suankotanki, YakuValue::Yakuman(2)));
+            han += HAN_2YAKUMAN;
+        } else {
+            yaku.push((Yaku::Suankotanki, YakuValue::Yakuman(1)));
             han += HAN_YAKUMAN;
         }
     }
     // 四暗刻
     if is_suanko(info, counts) {
-        yaku.insert(Yaku::Suanko);
+        yaku.push((Yaku::Suanko, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 緑一色
     if is_ryuiso(info, counts) {
-        yaku.insert(Yaku::Ryuiso);
+        yaku.push((Yaku::Ryuiso, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 清老頭
     if is_chinroto(info, counts) {
-        yaku.insert(Yaku::Chinroto);
+        yaku.push((Yaku::Chinroto, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 大四喜
     if is_daisushi(info, counts) {
-        yaku.insert(Yaku::Daisushi);
+        yaku.push((Yaku::Daisushi, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 小四喜
     if is_syosushi(info, counts) {
-        yaku.insert(Yaku::Syosushi);
+        yaku.push((Yaku::Syosushi, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 字一色
     if is_tsuiso(info, counts) {
-        yaku.insert(Yaku::Tsuiso);
+        yaku.push((Yaku::Tsuiso, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 四槓子
     if is_sukantsu(info, counts) {
-        yaku.insert(Yaku::Sukantsu);
+        yaku.push((Yaku::Sukantsu, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
     // 大三元
     if is_daisangen(info, counts) {
-        yaku.insert(Yaku::Daisangen);
+        yaku.push((Yaku::Daisangen, YakuValue::Yakuman(1)));
         han += HAN_YAKUMAN;
     }
 
     // 役満成立か？
     if han >= HAN_YAKUMAN {
+        yaku.sort_by_key(|(y, _)| *y);
         return Some(Score {
             han,
             fu: 0,
+            fu_detail: vec![],
             yaku,
-            point: calculate_point(info, han, 0),
+            point: calculate_point(info, rule, han, 0),
         });
     }
 
@@ -782,107 +1136,100 @@ fn calculate_yakuman_score(info: &AgariInformation, counts: &TileCount) -> Optio
 // 切り分けが不要な役の判定/翻計算
 fn calculate_margedand_yaku_han(
     info: &AgariInformation,
+    rule: &ScoreRuleConfig,
     counts: &TileCount,
-) -> (HashSet<Yaku>, i32) {
-    let mut yaku: HashSet<Yaku> = HashSet::new();
+) -> (Vec<(Yaku, YakuValue)>, i32) {
+    let mut yaku: Vec<(Yaku, YakuValue)> = vec![];
     let mut han = 0;
 
     // 門前自摸
     if info.hand.is_menzen() && info.tsumo {
-        yaku.insert(Yaku::Tsumo);
+        yaku.push((Yaku::Tsumo, YakuValue::Han(1)));
         han += 1;
     }
     // 立直/ダブルリーチ
     if info.riichi {
-        yaku.insert(Yaku::Riichi);
+        yaku.push((Yaku::Riichi, YakuValue::Han(1)));
         han += 1;
     } else if info.doubleriichi {
-        yaku.insert(Yaku::Doubleriichi);
+        yaku.push((Yaku::Doubleriichi, YakuValue::Han(2)));
         han += 2;
     }
     // 一発
     if info.ippatsu && (info.riichi || info.doubleriichi) {
-        yaku.insert(Yaku::Ippatsu);
+        yaku.push((Yaku::Ippatsu, YakuValue::Han(1)));
         han += 1;
     }
     // ドラ
-    let ndora = count_num_doras(info);
-    if ndora > 0 {
-        yaku.insert(Yaku::Dora);
-        han += ndora;
-    }
+    let (dora_yaku, dora_han) = calculate_dora_yaku_han(info);
+    yaku.extend(dora_yaku);
+    han += dora_han;
     // 海底摸月/河底撈魚
     if info.haitei {
         if info.tsumo {
-            yaku.insert(Yaku::Haiteitsumo);
+            yaku.push((Yaku::Haiteitsumo, YakuValue::Han(1)));
         } else {
-            yaku.insert(Yaku::Houteiron);
+            yaku.push((Yaku::Houteiron, YakuValue::Han(1)));
         }
         han += 1;
     }
     // 混老頭
     if is_honrouto(info, counts) {
-        yaku.insert(Yaku::Honrouto);
+        yaku.push((Yaku::Honrouto, YakuValue::Han(2)));
         han += 2;
     }
     // 清一色
     if is_chinitsu(info, counts) {
-        yaku.insert(Yaku::Chinitsu);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 6;
-        } else {
-            han += 5;
-        }
+        let n = if info.hand.is_menzen() { 6 } else { 5 };
+        yaku.push((Yaku::Chinitsu, YakuValue::Han(n)));
+        han += n;
     }
     // 混一色
     if is_honitsu(info, counts) {
-        yaku.insert(Yaku::Honitsu);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 3;
-        } else {
-            han += 2;
-        }
+        let n = if info.hand.is_menzen() { 3 } else { 2 };
+        yaku.push((Yaku::Honitsu, YakuValue::Han(n)));
+        han += n;
     }
     // 断么九
-    if is_tanyao(info, counts) {
-        yaku.insert(Yaku::Tanyao);
+    if is_tanyao(info, rule, counts) {
+        yaku.push((Yaku::Tanyao, YakuValue::Han(1)));
         han += 1;
     }
     // 三槓子
     if is_sankantsu(info, counts) {
-        yaku.insert(Yaku::Sankantsu);
+        yaku.push((Yaku::Sankantsu, YakuValue::Han(2)));
         han += 2;
     }
     // 小三元
     if is_syosangen(info, counts) {
-        yaku.insert(Yaku::Syosangen);
+        yaku.push((Yaku::Syosangen, YakuValue::Han(2)));
         han += 2;
     }
     // 槍槓
     if info.chankan {
-        yaku.insert(Yaku::Chankan);
+        yaku.push((Yaku::Chankan, YakuValue::Han(1)));
         han += 1;
     }
     // 嶺上開花
     if info.rinshan {
-        yaku.insert(Yaku::Rinshan);
+        yaku.push((Yaku::Rinshan, YakuValue::Han(1)));
         han += 1;
     }
     // 白
     if counts[&TileId::IdHaku] >= 3 {
-        yaku.insert(Yaku::Haku);
+        yaku.push((Yaku::Haku, YakuValue::Han(1)));
         han += 1;
     }
     // 發
     if counts[&TileId::IdHatu] >= 3 {
-        yaku.insert(Yaku::Hatu);
+        yaku.push((Yaku::Hatu, YakuValue::Han(1)));
         han += 1;
     }
     // 中
     if counts[&TileId::IdChun] >= 3 {
-        yaku.insert(Yaku::Chun);
+        yaku.push((Yaku::Chun, YakuValue::Han(1)));
         han += 1;
     }
     // 場風
@@ -891,7 +1238,7 @@ fn calculate_margedand_yaku_han(
         || (info.round == Wind::Sha && counts[&TileId::IdSha] >= 3)
         || (info.round == Wind::Pee && counts[&TileId::IdPee] >= 3)
     {
-        yaku.insert(Yaku::Bakaze);
+        yaku.push((Yaku::Bakaze, YakuValue::Han(1)));
         han += 1;
     }
     // 自風
@@ -900,7 +1247,7 @@ fn calculate_margedand_yaku_han(
         || (info.player == Wind::Sha && counts[&TileId::IdSha] >= 3)
         || (info.player == Wind::Pee && counts[&TileId::IdPee] >= 3)
     {
-        yaku.insert(Yaku::Jikaze);
+        yaku.push((Yaku::Jikaze, YakuValue::Han(1)));
         han += 1;
     }
 
@@ -910,100 +1257,325 @@ fn calculate_margedand_yaku_han(
 // 切り分けが必要な手の得点計算
 // 高点法に従い、最大の得点を持つ役/翻/符を持つ結果を返す
 fn calculate_dividedhand_score(info: &AgariInformation) -> ScoreTriple {
+    // 得点の初期化
+    let mut score = ScoreTriple {
+        han: 0,
+        fu: 0,
+        fu_detail: vec![],
+        yaku: vec![],
+    };
+
+    for div_hand in listup_dividedhands(&info.hand, info.wining_tile.id, info.tsumo) {
+        // 役/翻/符の更新
+        update_score_from_dividedhand(info, &div_hand, &mut score);
+    }
+
+    score
+}
+
+/// 通常形の和了について、全ての面子の切り分けとその得点を列挙
+///
+/// 役/翻には切り分けに依らない役（立直、ドラ等）も含む。
+/// 七対子/国士無双/役満として得点計算された場合は、いずれの解釈も採用されない。
+pub fn listup_agari_interpretations(
+    info: &AgariInformation,
+    rule: &ScoreRuleConfig,
+) -> Result<Vec<AgariInterpretation>, Error> {
+    let score = calculate_score_with_rules(info, rule)?;
+
+    // 切り分けが不要な役の判定
+    let marged_counts = calculate_tile_counts(
+        {
+            let mut tmp = info.hand.marged_tiles();
+            tmp.push(info.wining_tile.id);
+            tmp
+        }
+        .as_slice(),
+    );
+    let (marged_yaku, marged_han) = calculate_margedand_yaku_han(info, rule, &marged_counts);
+
+    let mut interpretations = vec![];
+    let mut selected = false;
+    for div_hand in listup_dividedhands(&info.hand, info.wining_tile.id, info.tsumo) {
+        let (mut yaku, mut han) = calculate_yaku_han_from_dividedhand(info, &div_hand);
+        let fu_detail = calculate_fu_from_dividedhand(info, &div_hand);
+        let fu = fu_detail.iter().map(|(_, f)| f).sum();
+        han += marged_han;
+        yaku.extend(marged_yaku.iter().cloned());
+        yaku.sort_by_key(|(y, _)| *y);
+        // 得点計算結果と一致する最初の解釈を採用されたものとする
+        let is_selected =
+            !selected && score.fu_detail == fu_detail && score.han == han && score.yaku == yaku;
+        selected |= is_selected;
+        interpretations.push(AgariInterpretation {
+            hand: div_hand,
+            han,
+            fu,
+            fu_detail,
+            yaku,
+            selected: is_selected,
+        });
+    }
+
+    Ok(interpretations)
+}
+
+/// 聴牌形（副露を含めて13枚）の待ち牌と待ちの形を列挙
+///
+/// 手牌で4枚全てを使っている牌は待ち牌に含めない。聴牌していなければ空を返す。
+pub fn listup_waiting_tiles(hand: &Hand) -> Result<Vec<WaitingTile>, Error> {
+    // 牌数チェック（槓子も3枚として数える）
+    if hand.hand.len() + 3 * hand.melds.len() != 13 {
+        return Err(Error::from(
+            "Cannot to listup waiting tiles: invalid number of tiles.",
+        ));
+    }
+
+    let marged_counts = calculate_tile_counts(hand.marged_tiles().as_slice());
+    let pure_tiles: Vec<TileId> = hand.hand.iter().map(|t| t.id).collect();
+    let mut waiting_tiles: Vec<WaitingTile> = vec![];
+    for w in TileId::ALL {
+        if marged_counts[&w] >= 4 {
+            continue;
+        }
+        // 通常形の切り分けから待ちの形を集める
+        let mut waits: Vec<Wait> = vec![];
+        for div_hand in listup_dividedhands(hand, w, true) {
+            if !waits.contains(&div_hand.wait) {
+                waits.push(div_hand.wait);
+            }
+        }
+        // 七対子/国士無双は単騎待ち
+        if hand.melds.is_empty() && !waits.contains(&Wait::Tanki) {
+            let appended = {
+                let mut tmp = pure_tiles.clone();
+                tmp.push(w);
+                tmp
+            };
+            if calculate_chitoitsu_shanten(&appended) == -1
+                || calculate_kokushimusou_shanten(&appended) == -1
+            {
+                waits.push(Wait::Tanki);
+            }
+        }
+        if !waits.is_empty() {
+            waiting_tiles.push(WaitingTile {
+                tile: w,
+                waits,
+                patterns: vec![],
+            });
+        }
+    }
+
+    // 複合形の判定
+    let has_wait = |waiting_tiles: &[WaitingTile], t: TileId, wait: Wait| {
+        waiting_tiles
+            .iter()
+            .any(|wt| wt.tile == t && wt.waits.contains(&wait))
+    };
+    let same_suit_nth = |t: TileId, n: i32| {
+        if t.is_suhai() && (1..=9).contains(&(t.getnumber() + n)) {
+            Some(t.nth(n))
+        } else {
+            None
+        }
+    };
+    let mut patterns: Vec<(TileId, WaitPattern)> = vec![];
+    for wt in &waiting_tiles {
+        let t = wt.tile;
+        // 三面張: 3つ飛びの3牌がいずれも両面待ち
+        if let (Some(t3), Some(t6)) = (same_suit_nth(t, 3), same_suit_nth(t, 6)) {
+            if [t, t3, t6]
+                .iter()
+                .all(|x| has_wait(&waiting_tiles, *x, Wait::Ryanmen))
+            {
+                patterns.extend([t, t3, t6].map(|x| (x, WaitPattern::Sanmenchan)));
+            }
+        }
+        // 延べ単: 3つ飛びの2牌がいずれも単騎待ち
+        if let Some(t3) = same_suit_nth(t, 3) {
+            if has_wait(&waiting_tiles, t, Wait::Tanki) && has_wait(&waiting_tiles, t3, Wait::Tanki)
+            {
+                patterns.extend([t, t3].map(|x| (x, WaitPattern::Nobetan)));
+            }
+        }
+        // 煙突: 双碰と両面を兼ねる牌があり、両面の相方と双碰の相方も含む
+        if wt.waits.contains(&Wait::Shanpon) && wt.waits.contains(&Wait::Ryanmen) {
+            patterns.push((t, WaitPattern::Entotsu));
+            for other in &waiting_tiles {
+                let is_ryanmen_pair = [-3, 3]
+                    .iter()
+                    .any(|n| same_suit_nth(t, *n) == Some(other.tile))
+                    && other.waits.contains(&Wait::Ryanmen);
+                if is_ryanmen_pair || other.waits.contains(&Wait::Shanpon) {
+                    patterns.push((other.tile, WaitPattern::Entotsu));
+                }
+            }
+        }
+    }
+    for wt in &mut waiting_tiles {
+        for (t, pattern) in &patterns {
+            if *t == wt.tile && !wt.patterns.contains(pattern) {
+                wt.patterns.push(*pattern);
+            }
+        }
+    }
+
+    Ok(waiting_tiles)
+}
+
+// 面子の中の和了牌の待ちの形を判定
+fn classify_wait(mentsu: &Mentsu, w: TileId) -> Option<Wait> {
+    match *mentsu {
+        // 双碰待ち
+        Mentsu::Anko { t } if t == w => Some(Wait::Shanpon),
+        // 順子の待ち
+        Mentsu::Syuntsu { min } if w.is_suhai() && w.gettype() == min.gettype() => {
+            match w as i32 - min as i32 {
+                0 if min.getnumber() == 7 => Some(Wait::Penchan),
+                0 => Some(Wait::Ryanmen),
+                1 => Some(Wait::Kanchan),
+                2 if min.getnumber() == 1 => Some(Wait::Penchan),
+                2 => Some(Wait::Ryanmen),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// 和了形の面子の切り分けを全て列挙
+fn listup_dividedhands(hand: &Hand, w: TileId, tsumo: bool) -> Vec<DividedHand> {
     // 副露牌を面子に読み替え
     let mut meld_mentsu: Vec<Mentsu> = vec![];
-    for m in &info.hand.melds {
+    for m in &hand.melds {
         match m {
-            Meld::Pung { tiles } => {
+            Meld::Pung { tiles, .. } => {
                 meld_mentsu.push(Mentsu::Pung { t: tiles[0].id });
             }
-            Meld::Chow { tiles } => {
+            Meld::Chow { tiles, .. } => {
                 meld_mentsu.push(Mentsu::Chow { min: tiles[0].id });
             }
             Meld::Ankan { tiles } => {
                 meld_mentsu.push(Mentsu::Ankan { t: tiles[0].id });
             }
-            Meld::Minkan { tiles } | Meld::Kakan { tiles } => {
+            Meld::Minkan { tiles, .. } | Meld::Kakan { tiles, .. } => {
                 meld_mentsu.push(Mentsu::Minkan { t: tiles[0].id });
             }
         }
     }
 
     // 純手牌+和了牌をカウント
-    let pure_counts = calculate_tile_counts(
+    let pure_counts = TileCountArray::from_tiles(
         {
-            let mut hand_counts = info.hand.hand.iter().map(|t| t.id).collect::<Vec<TileId>>();
-            hand_counts.push(info.wining_tile.id);
+            let mut hand_counts = hand.hand.iter().map(|t| t.id).collect::<Vec<TileId>>();
+            hand_counts.push(w);
             hand_counts
         }
         .as_slice(),
     );
 
-    // 得点の初期化
-    let mut score = ScoreTriple {
-        han: 0,
-        fu: 0,
-        yaku: HashSet::<Yaku>::new(),
-    };
+    // 頭を抜いて手牌の面子を切り分け
+    let heads: Vec<TileId> = pure_counts
+        .iter()
+        .filter(|(_, c)| *c >= 2)
+        .map(|(t, _)| t)
+        .collect();
+    let mut divided: Vec<(TileId, Vec<Mentsu>)> = vec![];
+    for t in heads {
+        let mut headless_counts = pure_counts;
+        headless_counts[t] -= 2;
+        let mut mentsu = vec![];
+        divide_mentsu(&mut headless_counts, &mut mentsu, &mut |m| {
+            divided.push((t, m.to_vec()));
+        });
+    }
 
-    // 手牌の面子を切り分けながら翻/符の計算
-    for (t, c) in &pure_counts {
-        if *c >= 2 {
-            let mut headless_counts = pure_counts.clone();
-            headless_counts[t] -= 2;
-            let mut div_hand = DividedHand {
-                atama: *t,
-                mentsu: meld_mentsu.clone(),
-            };
-            divide_mentsu(info, &mut headless_counts, &mut div_hand, &mut score)
+    // 和了牌を含むブロックごとに待ちの形を割り当て
+    let mut div_hands: Vec<DividedHand> = vec![];
+    let mut push_unique = |div_hand: DividedHand| {
+        if !div_hands.contains(&div_hand) {
+            div_hands.push(div_hand);
+        }
+    };
+    for (atama, closed) in divided {
+        // 和了牌を含む面子を置き換えて構成
+        let construct = |replaced: Option<(usize, Mentsu)>, wait: Wait| {
+            let mut all = meld_mentsu.clone();
+            all.extend(closed.iter().enumerate().map(|(i, m)| match replaced {
+                Some((index, mentsu)) if index == i => mentsu,
+                _ => *m,
+            }));
+            DividedHand {
+                atama,
+                mentsu: all,
+                wait,
+            }
+        };
+        // 単騎待ち
+        if atama == w {
+            push_unique(construct(None, Wait::Tanki));
+        }
+        for (i, m) in closed.iter().enumerate() {
+            match classify_wait(m, w) {
+                // 双碰待ち（ロン和了なら明刻扱い）
+                Some(Wait::Shanpon) => {
+                    let kotsu = if tsumo {
+                        Mentsu::Anko { t: w }
+                    } else {
+                        Mentsu::Pung { t: w }
+                    };
+                    push_unique(construct(Some((i, kotsu)), Wait::Shanpon));
+                }
+                Some(wait) => {
+                    push_unique(construct(Some((i, *m)), wait));
+                }
+                None => {}
+            }
         }
     }
 
-    score
+    div_hands
 }
 
+// 手牌から面子を切り出す（最小の牌から切り出すことで重複を防ぐ）
 fn divide_mentsu(
-    info: &AgariInformation,
-    remain_counts: &mut TileCount,
-    div_hand: &mut DividedHand,
-    score: &mut ScoreTriple,
+    remain_counts: &mut TileCountArray,
+    mentsu: &mut Vec<Mentsu>,
+    on_divided: &mut dyn FnMut(&[Mentsu]),
 ) {
-    // 面子が4つになったら切り分け終了
-    if div_hand.mentsu.len() >= 4 {
-        // 役/翻/符の更新
-        update_score_from_dividedhand(info, div_hand, score);
-        return;
-    }
+    // 残りの最小の牌を取得
+    let t = match remain_counts.0.iter().position(|c| *c > 0) {
+        Some(index) => TileId::from_index(index),
+        None => {
+            // 全て面子に切り分けられた
+            on_divided(mentsu);
+            return;
+        }
+    };
 
-    for (t, c) in remain_counts.iter() {
-        // 暗刻を抜き出して調べる
-        if *c >= 3 {
-            let mut ankoless = remain_counts.clone();
-            let kotsu = if (*t == info.wining_tile.id) && !info.tsumo {
-                // 和了牌の場合は明刻に
-                Mentsu::Pung { t: *t }
-            } else {
-                Mentsu::Anko { t: *t }
-            };
-            div_hand.mentsu.push(kotsu);
-            ankoless[t] -= 3;
-            divide_mentsu(info, &mut ankoless, div_hand, score);
-            div_hand.mentsu.pop();
-        }
-        // 順子を抜き出して調べる
-        if t.is_suhai()
-            && t.getnumber() <= 7
-            && remain_counts[&t.nth(0)] > 0
-            && remain_counts[&t.nth(1)] > 0
-            && remain_counts[&t.nth(2)] > 0
-        {
-            let mut syuntsuless = remain_counts.clone();
-            div_hand.mentsu.push(Mentsu::Syuntsu { min: *t });
-            syuntsuless[&t.nth(0)] -= 1;
-            syuntsuless[&t.nth(1)] -= 1;
-            syuntsuless[&t.nth(2)] -= 1;
-            divide_mentsu(info, &mut syuntsuless, div_hand, score);
-            div_hand.mentsu.pop();
+    // 暗刻を抜き出して調べる
+    if remain_counts[t] >= 3 {
+        remain_counts[t] -= 3;
+        mentsu.push(Mentsu::Anko { t });
+        divide_mentsu(remain_counts, mentsu, on_divided);
+        mentsu.pop();
+        remain_counts[t] += 3;
+    }
+    // 順子を抜き出して調べる
+    if t.is_suhai()
+        && t.getnumber() <= 7
+        && remain_counts[t.nth(1)] > 0
+        && remain_counts[t.nth(2)] > 0
+    {
+        for i in 0..3 {
+            remain_counts[t.nth(i)] -= 1;
+        }
+        mentsu.push(Mentsu::Syuntsu { min: t });
+        divide_mentsu(remain_counts, mentsu, on_divided);
+        mentsu.pop();
+        for i in 0..3 {
+            remain_counts[t.nth(i)] += 1;
         }
     }
 }
@@ -1016,12 +1588,14 @@ fn update_score_from_dividedhand(
 ) {
     // 役/翻/符の計算
     let (yaku, han) = calculate_yaku_han_from_dividedhand(info, div_hand);
-    let fu = calculate_fu_from_dividedhand(info, div_hand);
+    let fu_detail = calculate_fu_from_dividedhand(info, div_hand);
+    let fu = fu_detail.iter().map(|(_, f)| f).sum();
 
     // これまでで最大の翻/符であれば得点情報を更新
     if (han > max_score.han) || (han == max_score.han && fu > max_score.fu) {
         max_score.han = han;
         max_score.fu = fu;
+        max_score.fu_detail = fu_detail;
         max_score.yaku = yaku;
     }
 }
@@ -1030,182 +1604,164 @@ fn update_score_from_dividedhand(
 fn calculate_yaku_han_from_dividedhand(
     info: &AgariInformation,
     div_hand: &DividedHand,
-) -> (HashSet<Yaku>, i32) {
-    let mut yaku: HashSet<Yaku> = HashSet::new();
+) -> (Vec<(Yaku, YakuValue)>, i32) {
+    let mut yaku: Vec<(Yaku, YakuValue)> = vec![];
     let mut han = 0;
 
     // 平和
     if is_pinfu(info, div_hand) {
-        yaku.insert(Yaku::Pinfu);
+        yaku.push((Yaku::Pinfu, YakuValue::Han(1)));
         han += 1;
     }
     // 二盃口
     if is_ryanpeko(info, div_hand) {
-        yaku.insert(Yaku::Ryanpeko);
+        yaku.push((Yaku::Ryanpeko, YakuValue::Han(3)));
         han += 3;
     }
     // 一盃口
     if is_ipeko(info, div_hand) {
-        yaku.insert(Yaku::Ipeko);
+        yaku.push((Yaku::Ipeko, YakuValue::Han(1)));
         han += 1;
     }
     // 一気通貫
     if is_ikkitsukan(info, div_hand) {
-        yaku.insert(Yaku::Ikkitsukan);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 2;
-        } else {
-            han += 1;
-        }
+        let n = if info.hand.is_menzen() { 2 } else { 1 };
+        yaku.push((Yaku::Ikkitsukan, YakuValue::Han(n)));
+        han += n;
     }
     // 三色同順
     if is_sansyokudoujyun(info, div_hand) {
-        yaku.insert(Yaku::Sansyokudoujyun);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 2;
-        } else {
-            han += 1;
-        }
+        let n = if info.hand.is_menzen() { 2 } else { 1 };
+        yaku.push((Yaku::Sansyokudoujyun, YakuValue::Han(n)));
+        han += n;
     }
     // 三色同刻
     if is_sansyokudoukoku(info, div_hand) {
-        yaku.insert(Yaku::Sansyokudoukoku);
+        yaku.push((Yaku::Sansyokudoukoku, YakuValue::Han(2)));
         han += 2;
     }
     // 純全帯么九
     if is_jyunchanta(info, div_hand) {
-        yaku.insert(Yaku::Jyunchanta);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 3;
-        } else {
-            han += 2;
-        }
+        let n = if info.hand.is_menzen() { 3 } else { 2 };
+        yaku.push((Yaku::Jyunchanta, YakuValue::Han(n)));
+        han += n;
     }
     // 混全帯么九
     if is_chanta(info, div_hand) {
-        yaku.insert(Yaku::Chanta);
         // 食い下がり
-        if info.hand.is_menzen() {
-            han += 2;
-        } else {
-            han += 1;
-        }
+        let n = if info.hand.is_menzen() { 2 } else { 1 };
+        yaku.push((Yaku::Chanta, YakuValue::Han(n)));
+        han += n;
     }
     // 対々和
     if is_toitoiho(info, div_hand) {
-        yaku.insert(Yaku::Toitoiho);
+        yaku.push((Yaku::Toitoiho, YakuValue::Han(2)));
         han += 2;
     }
     // 三暗刻
     if is_sananko(info, div_hand) {
-        yaku.insert(Yaku::Sananko);
+        yaku.push((Yaku::Sananko, YakuValue::Han(2)));
         han += 2;
     }
 
     (yaku, han)
 }
 
-// 符計算
-fn calculate_fu_from_dividedhand(info: &AgariInformation, div_hand: &DividedHand) -> i32 {
+// 符計算（内訳を返す）
+fn calculate_fu_from_dividedhand(
+    info: &AgariInformation,
+    div_hand: &DividedHand,
+) -> Vec<(Fu, i32)> {
+    // 副底による符
+    let mut fu_detail = vec![(Fu::Futei, 20)];
+
     // 平和
     if is_pinfu(info, div_hand) {
-        return if info.tsumo { 20 } else { 30 };
+        fu_detail.push((Fu::Pinfu, 0));
+        if !info.tsumo {
+            fu_detail.push((Fu::Menzen, 10));
+        }
+        return fu_detail;
     }
 
-    // 副底による符
-    let mut fu = 20;
     // 門前による符
     if info.hand.is_menzen() && !info.tsumo {
-        fu += 10;
+        fu_detail.push((Fu::Menzen, 10));
     }
     // 自摸による符
     if info.tsumo {
-        fu += 2;
+        fu_detail.push((Fu::Tsumo, 2));
     }
     // 待ち牌による符
-    if info.wining_tile.id == div_hand.atama {
-        fu += 2;
-    } else if info.wining_tile.id.is_suhai() {
-        for m in &div_hand.mentsu {
-            let min = match m {
-                Mentsu::Chow { min } | Mentsu::Syuntsu { min } => min,
-                _ => {
-                    continue;
-                }
-            };
-            // 辺張/嵌張待ち
-            if (info.wining_tile.id.getnumber() == 3 && info.wining_tile.id == min.nth(2))
-                || (info.wining_tile.id.getnumber() == 7 && info.wining_tile.id == min.nth(0))
-                || info.wining_tile.id == min.nth(1)
-            {
-                fu += 2;
-                break;
-            }
-        }
+    match div_hand.wait {
+        Wait::Tanki => fu_detail.push((Fu::Tanki, 2)),
+        Wait::Kanchan => fu_detail.push((Fu::Kanchan, 2)),
+        Wait::Penchan => fu_detail.push((Fu::Penchan, 2)),
+        Wait::Ryanmen | Wait::Shanpon => {}
     }
     // 雀頭の状態による符
-    // 役牌
-    if div_hand.atama.is_sangen()
-        || (div_hand.atama == TileId::IdTon && info.round == Wind::Ton)
-        || (div_hand.atama == TileId::IdNan && info.round == Wind::Nan)
-        || (div_hand.atama == TileId::IdSha && info.round == Wind::Sha)
-        || (div_hand.atama == TileId::IdPee && info.round == Wind::Pee)
-    {
-        fu += 2;
-    }
-    // 連風牌
-    if (div_hand.atama == TileId::IdTon && info.player == Wind::Ton)
-        || (div_hand.atama == TileId::IdNan && info.player == Wind::Nan)
-        || (div_hand.atama == TileId::IdSha && info.player == Wind::Sha)
-        || (div_hand.atama == TileId::IdPee && info.player == Wind::Pee)
-    {
-        fu += 2;
+    let atama_fu = calculate_atama_fu(info, div_hand.atama);
+    if atama_fu > 0 {
+        fu_detail.push((Fu::YakuhaiAtama { t: div_hand.atama }, atama_fu));
     }
     // 面子の構成による符
     for m in &div_hand.mentsu {
         match m {
             Mentsu::Anko { t } => {
-                if t.is_yaochu() {
-                    fu += 8;
-                } else {
-                    fu += 4
-                }
+                fu_detail.push((Fu::Anko { t: *t }, if t.is_yaochu() { 8 } else { 4 }));
             }
             Mentsu::Pung { t } => {
-                if t.is_yaochu() {
-                    fu += 4;
-                } else {
-                    fu += 2;
-                }
+                fu_detail.push((Fu::Minko { t: *t }, if t.is_yaochu() { 4 } else { 2 }));
             }
             Mentsu::Ankan { t } => {
-                if t.is_yaochu() {
-                    fu += 32;
-                } else {
-                    fu += 16;
-                }
+                fu_detail.push((Fu::Ankan { t: *t }, if t.is_yaochu() { 32 } else { 16 }));
             }
             Mentsu::Minkan { t } => {
-                if t.is_yaochu() {
-                    fu += 16;
-                } else {
-                    fu += 8;
-                }
+                fu_detail.push((Fu::Minkan { t: *t }, if t.is_yaochu() { 16 } else { 8 }));
             }
             _ => {}
         }
     }
 
     // ここまで副底の加符がないなら、30符に
+    let fu: i32 = fu_detail.iter().map(|(_, f)| f).sum();
     if fu == 20 {
-        fu = 30;
+        fu_detail.push((Fu::KuiPinfu, 10));
+        return fu_detail;
     }
 
     // 1の位を切り上げたものが最終結果
-    roundup(fu, 10)
+    if roundup(fu, 10) > fu {
+        fu_detail.push((Fu::Roundup, roundup(fu, 10) - fu));
+    }
+
+    fu_detail
+}
+
+// 雀頭の符計算
+fn calculate_atama_fu(info: &AgariInformation, atama: TileId) -> i32 {
+    let mut atama_fu = 0;
+    // 役牌
+    if atama.is_sangen()
+        || (atama == TileId::IdTon && info.round == Wind::Ton)
+        || (atama == TileId::IdNan && info.round == Wind::Nan)
+        || (atama == TileId::IdSha && info.round == Wind::Sha)
+        || (atama == TileId::IdPee && info.round == Wind::Pee)
+    {
+        atama_fu += 2;
+    }
+    // 連風牌
+    if (atama == TileId::IdTon && info.player == Wind::Ton)
+        || (atama == TileId::IdNan && info.player == Wind::Nan)
+        || (atama == TileId::IdSha && info.player == Wind::Sha)
+        || (atama == TileId::IdPee && info.player == Wind::Pee)
+    {
+        atama_fu += 2;
+    }
+    atama_fu
 }
 
 // 盃口数（同一順子数）のカウント
@@ -1232,53 +1788,22 @@ fn is_pinfu(info: &AgariInformation, hand: &DividedHand) -> bool {
         return false;
     }
 
-    // 雀頭が役牌ではないか？
-    match hand.atama {
-        TileId::IdHaku | TileId::IdHatu | TileId::IdChun => {
-            return false;
-        }
-        TileId::IdTon => {
-            if info.player == Wind::Ton {
-                return false;
-            }
-        }
-        TileId::IdNan => {
-            if info.player == Wind::Nan {
-                return false;
-            }
-        }
-        TileId::IdSha => {
-            if info.player == Wind::Sha {
-                return false;
-            }
-        }
-        TileId::IdPee => {
-            if info.player == Wind::Pee {
-                return false;
-            }
-        }
-        _ => {}
+    // 雀頭が役牌（符がつく牌）ではないか？
+    if calculate_atama_fu(info, hand.atama) > 0 {
+        return false;
     }
 
-    // 面子のチェック
-    let mut is_established = false;
-    for m in &hand.mentsu {
-        match m {
-            Mentsu::Syuntsu { min } => {
-                if (info.wining_tile.id == min.nth(0) && min.nth(1).getnumber() != 8)
-                    || (info.wining_tile.id == min.nth(2) && min.getnumber() != 1)
-                {
-                    is_established = true;
-                }
-            }
-            _ => {
-                // 順子以外が出現
-                return false;
-            }
-        }
+    // 順子以外が出現
+    if hand
+        .mentsu
+        .iter()
+        .any(|m| !matches!(m, Mentsu::Syuntsu { .. }))
+    {
+        return false;
     }
 
-    is_established
+    // 両面待ちを要求
+    hand.wait == Wait::Ryanmen
 }
 
 // 一盃口が成立しているか？
@@ -1302,16 +1827,15 @@ fn is_sansyokudoujyun(_info: &AgariInformation, hand: &DividedHand) -> bool {
                         match hand.mentsu[j] {
                             Mentsu::Syuntsu { min: tj } | Mentsu::Chow { min: tj } => {
                                 match hand.mentsu[k] {
-                                    Mentsu::Syuntsu { min: tk } | Mentsu::Chow { min: tk } => {
-                                        // 同じ数字かつ種類が全て異なる
+                                    // 同じ数字かつ種類が全て異なる
+                                    Mentsu::Syuntsu { min: tk } | Mentsu::Chow { min: tk }
                                         if (ti.getnumber() == tj.getnumber())
                                             && (tj.getnumber() == tk.getnumber())
                                             && (ti != tj)
                                             && (tj != tk)
-                                            && (tk != ti)
-                                        {
-                                            return true;
-                                        }
+                                            && (tk != ti) =>
+                                    {
+                                        return true;
                                     }
                                     _ => {}
                                 }
@@ -1471,10 +1995,10 @@ fn is_sansyokudoukoku(_info: &AgariInformation, hand: &DividedHand) -> bool {
                     Mentsu::Pung { t: tj }
                     | Mentsu::Anko { t: tj }
                     | Mentsu::Minkan { t: tj }
-                    | Mentsu::Ankan { t: tj } => {
-                        if ti.is_suhai() && tj.is_suhai() && (ti.getnumber() == tj.getnumber()) {
-                            num_dokoku += 1;
-                        }
+                    | Mentsu::Ankan { t: tj }
+                        if ti.is_suhai() && tj.is_suhai() && (ti.getnumber() == tj.getnumber()) =>
+                    {
+                        num_dokoku += 1;
                     }
                     _ => {}
                 },
@@ -1529,9 +2053,9 @@ fn is_ryanpeko(info: &AgariInformation, hand: &DividedHand) -> bool {
 }
 
 // 断么九が成立しているか？
-fn is_tanyao(info: &AgariInformation, counts: &TileCount) -> bool {
+fn is_tanyao(info: &AgariInformation, rule: &ScoreRuleConfig, counts: &TileCount) -> bool {
     // 喰いタン判定
-    if !RULE_CONFIG.read().unwrap().kuitan && !info.hand.is_menzen() {
+    if !rule.kuitan && !info.hand.is_menzen() {
         return false;
     }
 
@@ -1791,11 +2315,9 @@ fn is_suankotanki(info: &AgariInformation, counts: &TileCount) -> bool {
             3 => {
                 num_anko += 1;
             }
-            2 => {
-                // 和了牌のはず
-                if *t != info.wining_tile.id {
-                    return false;
-                }
+            // 対子は和了牌のはず
+            2 if *t != info.wining_tile.id => {
+                return false;
             }
             _ => {}
         }
@@ -0,0 +1,225 @@
+use crate::score::*;
+use crate::types::*;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// 不聴罰符の総額
+pub const NOTEN_PENALTY: i32 = 3000;
+
+/// 荒牌流局の精算結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RyukyokuSettlement {
+    /// 各席の聴牌状態
+    pub tenpai: [bool; 4],
+    /// 流し満貫を達成した席
+    pub nagashimangan: Vec<usize>,
+    /// 各席の点数の増減（供託立直棒を除く）
+    pub deltas: [i32; 4],
+}
+
+/// 複数人がロンしたときの扱い
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MultipleRonRule {
+    /// ダブロン/トリロンを認める
+    MultipleRon,
+    /// 頭ハネ（放銃者の下家から順に最初の1人のみ和了）
+    #[default]
+    Atamahane,
+}
+
+/// 途中流局ルール（真なら途中流局とする）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AbortiveDrawConfig {
+    /// 九種九牌
+    pub kyuushu_kyuuhai: bool,
+    /// 四風連打
+    pub suufon_renda: bool,
+    /// 四家立直
+    pub suucha_riichi: bool,
+    /// 四開槓（1人で4回槓した場合を除く）
+    pub suukaikan: bool,
+    /// 三家和
+    pub sanchahou: bool,
+}
+
+/// 途中流局の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AbortiveDraw {
+    /// 九種九牌（宣言した席）
+    KyuushuKyuuhai { seat: usize },
+    /// 四風連打
+    SuufonRenda,
+    /// 四家立直
+    SuuchaRiichi,
+    /// 四開槓
+    Suukaikan,
+    /// 三家和
+    Sanchahou,
+}
+
+/// 1枚の牌に対するロンの精算結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RonSettlement {
+    /// 和了者（放銃者の下家から順。先頭が本場と供託を受け取る）
+    pub winners: Vec<usize>,
+    /// 各席の点数の増減（供託立直棒の受け取りを含む）
+    pub deltas: [i32; 4],
+}
+
+impl Default for AbortiveDrawConfig {
+    fn default() -> Self {
+        AbortiveDrawConfig {
+            kyuushu_kyuuhai: true,
+            suufon_renda: true,
+            suucha_riichi: true,
+            suukaikan: true,
+            sanchahou: true,
+        }
+    }
+}
+
+/// 河が流し満貫の条件を満たすか？（全て么九牌で、1枚も鳴かれていない）
+pub fn is_nagashimangan(discards: &[DiscardedTile]) -> bool {
+    !discards.is_empty() && discards.iter().all(|d| d.tile.id.is_yaochu() && !d.called)
+}
+
+/// 不聴罰符の支払い（聴牌者が3000点を等分して受け取る）
+pub fn calculate_noten_penalty(tenpai: [bool; 4]) -> [i32; 4] {
+    let num_tenpai = tenpai.iter().filter(|t| **t).count() as i32;
+    if num_tenpai == 0 || num_tenpai == 4 {
+        return [0; 4];
+    }
+    tenpai.map(|t| {
+        if t {
+            NOTEN_PENALTY / num_tenpai
+        } else {
+            -NOTEN_PENALTY / (4 - num_tenpai)
+        }
+    })
+}
+
+/// 荒牌流局の精算
+///
+/// 流し満貫が成立すれば満貫の自摸和了と同じ支払いを受け取り（本場と供託は含まない）、
+/// 不聴罰符は支払わない。成立しなければ不聴罰符を精算する。
+pub fn settle_exhaustive_draw(
+    tenpai: [bool; 4],
+    discards: [&[DiscardedTile]; 4],
+    dealer: usize,
+    rule: &ScoreRuleConfig,
+) -> RyukyokuSettlement {
+    let nagashimangan: Vec<usize> = if rule.nagashimangan {
+        (0..4).filter(|s| is_nagashimangan(discards[*s])).collect()
+    } else {
+        vec![]
+    };
+    if nagashimangan.is_empty() {
+        return RyukyokuSettlement {
+            tenpai,
+            nagashimangan,
+            deltas: calculate_noten_penalty(tenpai),
+        };
+    }
+
+    // 満貫の自摸和了と同じ支払い
+    let mut deltas = [0; 4];
+    for winner in &nagashimangan {
+        for payer in (0..4).filter(|s| s != winner) {
+            let payment = if *winner == dealer || payer == dealer {
+                4000
+            } else {
+                2000
+            };
+            deltas[payer] -= payment;
+            deltas[*winner] += payment;
+        }
+    }
+    RyukyokuSettlement {
+        tenpai,
+        nagashimangan,
+        deltas,
+    }
+}
+
+/// ロンを宣言した席から和了者を決める（放銃者の下家から順）
+///
+/// sanchahouが真で3人がロンした場合は三家和の途中流局としてNoneを返す。
+pub fn select_ron_winners(
+    loser: usize,
+    ron: [bool; 4],
+    multiple_ron: MultipleRonRule,
+    sanchahou: bool,
+) -> Option<Vec<usize>> {
+    let winners: Vec<usize> = (1..4)
+        .map(|i| (loser + i) % 4)
+        .filter(|s| ron[*s])
+        .collect();
+    if sanchahou && winners.len() == 3 {
+        return None;
+    }
+    match multiple_ron {
+        MultipleRonRule::MultipleRon => Some(winners),
+        MultipleRonRule::Atamahane => Some(winners.into_iter().take(1).collect()),
+    }
+}
+
+/// 1枚の牌に対するロンの精算（三家和ならNone）
+///
+/// winsは和了者と局の本場で計算した得点計算結果の組。放銃者は各和了者に支払い、
+/// 本場と供託立直棒は放銃者の下家から最も近い和了者のみが受け取る（上家取り）。
+pub fn settle_ron(
+    loser: usize,
+    wins: &[(usize, &Score)],
+    nhonba: i32,
+    nriichi: i32,
+    rule: &ScoreRuleConfig,
+    multiple_ron: MultipleRonRule,
+    sanchahou: bool,
+) -> Result<Option<RonSettlement>, Error> {
+    if loser >= 4 {
+        return Err(Error::from("Invalid ron: invalid loser seat."));
+    }
+    let mut ron = [false; 4];
+    for (winner, _) in wins {
+        if *winner >= 4 || *winner == loser || ron[*winner] {
+            return Err(Error::from(format!(
+                "Invalid ron: invalid or duplicated winner seat {}.",
+                winner
+            )));
+        }
+        ron[*winner] = true;
+    }
+    if wins.is_empty() {
+        return Err(Error::from("Invalid ron: no winners."));
+    }
+
+    let winners = match select_ron_winners(loser, ron, multiple_ron, sanchahou) {
+        Some(winners) => winners,
+        None => return Ok(None),
+    };
+    let tsumibo_point = if rule.ba1500 { 1500 } else { 300 } * nhonba;
+    let mut deltas = [0; 4];
+    for (i, winner) in winners.iter().enumerate() {
+        let (_, score) = wins.iter().find(|(w, _)| w == winner).unwrap();
+        let mut payment = match score.point.feed {
+            Feed::Duck { point } => point,
+            _ => {
+                return Err(Error::from("Invalid ron: payment does not match with ron."));
+            }
+        };
+        if i == 0 {
+            deltas[*winner] += 1000 * nriichi;
+        } else {
+            payment -= tsumibo_point;
+        }
+        deltas[loser] -= payment;
+        deltas[*winner] += payment;
+    }
+    Ok(Some(RonSettlement { winners, deltas }))
+}
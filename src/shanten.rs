@@ -1,26 +1,59 @@
 use crate::types::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
 
-include!(concat!(env!("OUT_DIR"), "/shanten_hash_table.rs"));
+include!(concat!(env!("OUT_DIR"), "/shanten_table.rs"));
+
+/// 打牌候補の評価結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiscardCandidate {
+    /// 打牌
+    pub discard: TileId,
+    /// 打牌後の向聴数
+    pub shanten: i32,
+    /// 打牌後の有効牌と残り枚数
+    pub effective_tiles: Vec<(TileId, i32)>,
+}
+
+impl DiscardCandidate {
+    /// 有効牌の残り枚数の合計
+    pub fn num_effective_tiles(&self) -> i32 {
+        self.effective_tiles.iter().map(|(_, n)| n).sum()
+    }
+}
 
 /// 通常手の向聴数を計算（1で一向聴, 0で聴牌, -1で和了）
+///
+/// 13枚に満たない手牌は、不足分を副露した面子とみなして計算する。
+/// 同じ数牌が5枚以上あるとパニックするため、入力が不確かならcalculate_hand_shantenを使う。
 pub fn calculate_normal_shanten(tiles: &[TileId]) -> i32 {
-    let counts = calculate_tile_counts(tiles);
-    let mut min_shanten = calculate_normal_shanten_use_table(&counts);
+    calculate_normal_shanten_from_counts(
+        &TileCountArray::from_tiles(tiles),
+        count_implicit_melds(tiles),
+    )
+}
+
+/// 純手牌の枚数から副露数を求める
+fn count_implicit_melds(tiles: &[TileId]) -> i32 {
+    (4 - tiles.len() as i32 / 3).max(0)
+}
+
+/// 出現回数から通常手の向聴数を計算
+fn calculate_normal_shanten_from_counts(counts: &TileCountArray, num_melds: i32) -> i32 {
+    let mut min_shanten = calculate_normal_shanten_use_table(counts, num_melds);
 
     // 頭を抜いて調べる
-    let mult_counts = counts
-        .iter()
-        .filter(|(_, c)| **c >= 2)
-        .collect::<HashMap<_, _>>();
-    for t in mult_counts.keys() {
-        let mut headless = counts.clone();
-        headless[t] -= 2;
-        let shanten = calculate_normal_shanten_use_table(&headless) - 1;
-        if shanten < min_shanten {
-            min_shanten = shanten;
+    let mut headless = *counts;
+    for t in TileId::ALL {
+        if headless[t] >= 2 {
+            headless[t] -= 2;
+            let shanten = calculate_normal_shanten_use_table(&headless, num_melds) - 1;
+            if shanten < min_shanten {
+                min_shanten = shanten;
+            }
+            headless[t] += 2;
         }
     }
 
@@ -28,40 +61,27 @@ pub fn calculate_normal_shanten(tiles: &[TileId]) -> i32 {
 }
 
 /// 通常手の向聴数をテーブル引きにより計算
-fn calculate_normal_shanten_use_table(counts: &TileCount) -> i32 {
+fn calculate_normal_shanten_use_table(counts: &TileCountArray, num_melds: i32) -> i32 {
     let mut num_mentsu = 0;
     let mut num_tatsu = 0;
 
     // 数牌の並びから面子と塔子を数える
     for ty in [TileType::MANZU, TileType::PINZU, TileType::SOUZU] {
-        let typed_count: HashMap<_, _> = counts
-            .iter()
-            .filter(|(t, _)| t.is_suhai() && (t.gettype() == ty))
-            .collect();
-        let mut key: Vec<char> = "000000000".chars().collect();
-        for (t, v) in typed_count.iter() {
-            key[(t.getnumber() - 1) as usize] = char::from_digit(**v as u32, 10).unwrap();
-        }
-        let keystr = String::from_iter(key.iter());
-        let (nments, ntatsu) = SHANTEN_HASH.get(&*keystr).unwrap();
-        num_mentsu += nments;
+        let (nmentsu, ntatsu) = lookup_suhai_table(counts.suhai(ty));
+        num_mentsu += nmentsu;
         num_tatsu += ntatsu;
     }
 
     // 字牌は刻子と対子だけ数えればOK
-    let ji_count: HashMap<_, _> = counts.iter().filter(|(t, _)| t.is_jihai()).collect();
-    for c in ji_count.values() {
-        match **c {
-            2 => {
-                num_tatsu += 1;
-            }
-            cnt if cnt > 2 => {
-                num_mentsu += 1;
-            }
-            _ => {}
-        }
+    for c in counts.jihai() {
+        let (nmentsu, ntatsu) = JIHAI_SHANTEN_TABLE[(*c as usize).min(4)];
+        num_mentsu += nmentsu;
+        num_tatsu += ntatsu;
     }
 
+    // 副露を面子として数える
+    num_mentsu += num_melds;
+
     // 刻子+塔子が4を超ていたら4に制限
     if (num_mentsu + num_tatsu) > 4 {
         num_tatsu = 4 - num_mentsu
@@ -70,11 +90,23 @@ fn calculate_normal_shanten_use_table(counts: &TileCount) -> i32 {
     8 - 2 * num_mentsu - num_tatsu
 }
 
+/// 数牌1種類の枚数の並びから面子数と塔子数をテーブル引き（各枚数は4以下であること）
+fn lookup_suhai_table(counts: &[u8]) -> (i32, i32) {
+    // 一から九の枚数を5進数で符号化
+    let index = counts.iter().fold(0, |key, c| key * 5 + *c as usize);
+    let entry = SHANTEN_TABLE[index];
+    ((entry >> 4) as i32, (entry & 0xF) as i32)
+}
+
 /// 七対子手の向聴数を計算（1で一向聴, 0で聴牌, -1で和了）
 pub fn calculate_chitoitsu_shanten(tiles: &[TileId]) -> i32 {
-    let counts = calculate_tile_counts(tiles);
-    let num_toitsu = counts.iter().filter(|(_, c)| **c >= 2).count() as i32;
-    let num_types = counts.iter().filter(|(_, c)| **c >= 1).count() as i32;
+    calculate_chitoitsu_shanten_from_counts(&TileCountArray::from_tiles(tiles))
+}
+
+/// 出現回数から七対子手の向聴数を計算
+fn calculate_chitoitsu_shanten_from_counts(counts: &TileCountArray) -> i32 {
+    let num_toitsu = counts.0.iter().filter(|c| **c >= 2).count() as i32;
+    let num_types = counts.0.iter().filter(|c| **c >= 1).count() as i32;
     let mut shanten = 6 - num_toitsu;
 
     // 3毎持ち以上の面子は無効なので向聴数を増やす
@@ -87,24 +119,43 @@ pub fn calculate_chitoitsu_shanten(tiles: &[TileId]) -> i32 {
 
 /// 国士無双手の向聴数を計算（1で一向聴, 0で聴牌, -1で和了）
 pub fn calculate_kokushimusou_shanten(tiles: &[TileId]) -> i32 {
-    let counts = calculate_tile_counts(tiles);
+    calculate_kokushimusou_shanten_from_counts(&TileCountArray::from_tiles(tiles))
+}
+
+/// 出現回数から国士無双手の向聴数を計算
+fn calculate_kokushimusou_shanten_from_counts(counts: &TileCountArray) -> i32 {
     // 么九牌を数える
-    let yaochu_counts: HashMap<_, _> = counts
+    let yaochu_counts: Vec<u8> = counts
         .iter()
-        .filter(|(t, c)| **c >= 1 && t.is_yaochu())
+        .filter(|(t, c)| *c >= 1 && t.is_yaochu())
+        .map(|(_, c)| c)
         .collect();
     // 頭があるか？
-    let head = yaochu_counts.iter().any(|(_, c)| **c >= 2) as i32;
+    let head = yaochu_counts.iter().any(|c| *c >= 2) as i32;
 
-    13 - yaochu_counts.keys().len() as i32 - head
+    13 - yaochu_counts.len() as i32 - head
 }
 
 /// 通常/七対子/国士無双手の中で最小の向聴数を計算（1で一向聴, 0で聴牌, -1で和了）
+///
+/// 13枚に満たない手牌は副露しているとみなし、通常手の向聴数のみを計算する。
+/// 同じ数牌が5枚以上あるとパニックするため、入力が不確かならcalculate_hand_shantenを使う。
 pub fn calculate_shanten(tiles: &[TileId]) -> i32 {
+    calculate_shanten_from_counts(
+        &TileCountArray::from_tiles(tiles),
+        count_implicit_melds(tiles),
+    )
+}
+
+/// 出現回数から最小の向聴数を計算
+fn calculate_shanten_from_counts(counts: &TileCountArray, num_melds: i32) -> i32 {
+    if num_melds > 0 {
+        return calculate_normal_shanten_from_counts(counts, num_melds);
+    }
     *[
-        calculate_normal_shanten(tiles),
-        calculate_chitoitsu_shanten(tiles),
-        calculate_kokushimusou_shanten(tiles),
+        calculate_normal_shanten_from_counts(counts, num_melds),
+        calculate_chitoitsu_shanten_from_counts(counts),
+        calculate_kokushimusou_shanten_from_counts(counts),
     ]
     .iter()
     .min()
@@ -114,39 +165,48 @@ pub fn calculate_shanten(tiles: &[TileId]) -> i32 {
 /// 有効牌（向聴数を下げる牌）を列挙
 fn listup_effective_tiles_common(
     tiles: &[TileId],
-    candidate_tiles: &[TileId],
-    shanten_calculator: &dyn Fn(&[TileId]) -> i32,
+    candidate_tiles: &HashSet<TileId>,
+    shanten_calculator: &dyn Fn(&TileCountArray) -> i32,
 ) -> Result<HashSet<TileId>, Error> {
-    let current_shanten = shanten_calculator(tiles);
+    let mut counts = TileCountArray::from_tiles(tiles);
+    let current_shanten = shanten_calculator(&counts);
     let mut effective_tiles: HashSet<TileId> = HashSet::new();
     for ct in candidate_tiles {
-        let mut appended = tiles.to_vec();
-        appended.push(*ct);
-        if current_shanten > shanten_calculator(appended.as_slice()) {
+        // 5枚目は自摸できない
+        if counts[*ct] >= 4 {
+            continue;
+        }
+        counts[*ct] += 1;
+        if current_shanten > shanten_calculator(&counts) {
             effective_tiles.insert(*ct);
         }
+        counts[*ct] -= 1;
     }
     Ok(effective_tiles)
 }
 
-/// 通常手の有効牌候補を列挙
-fn listup_normal_candidate_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error> {
-    let counts = calculate_tile_counts(tiles);
-    let sum_counts = counts.iter().fold(0, |sum, c| sum + c.1);
-
-    // 牌数チェック（自摸した後であることを要求）
-    if !(((sum_counts % 3) == 1) && (sum_counts >= 1) && (sum_counts < 14)) {
+/// 牌数チェック（自摸した後であることを要求）
+fn check_candidate_tiles(counts: &TileCountArray) -> Result<(), Error> {
+    let sum_counts = counts.sum();
+    if !(((sum_counts % 3) == 1) && (1..14).contains(&sum_counts)) {
         return Err(Error::from(
             "Cannot to calculate effective tiles: invalid number of tiles.",
         ));
     }
+    check_tile_counts(counts)
+}
+
+/// 通常手の有効牌候補を列挙
+fn listup_normal_candidate_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error> {
+    let counts = TileCountArray::from_tiles(tiles);
+    check_candidate_tiles(&counts)?;
 
     // 候補牌の列挙
     let mut candidate_tiles = HashSet::new();
-    for (t, c) in &counts {
-        if *c > 0 {
+    for (t, c) in counts.iter() {
+        if c > 0 {
             // 2枚以上になるときは候補
-            candidate_tiles.insert(*t);
+            candidate_tiles.insert(t);
             // 順子候補の列挙
             if t.is_suhai() {
                 match t.getnumber() {
@@ -184,41 +244,22 @@ fn listup_normal_candidate_effective_tiles(tiles: &[TileId]) -> Result<HashSet<T
 
 /// 七対子手の有効牌候補を列挙
 fn listup_chitoitsu_candidate_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error> {
-    let counts = calculate_tile_counts(tiles);
-    let sum_counts = counts.iter().fold(0, |sum, c| sum + c.1);
+    let counts = TileCountArray::from_tiles(tiles);
+    check_candidate_tiles(&counts)?;
 
-    // 牌数チェック（自摸した後であることを要求）
-    if !(((sum_counts % 3) == 1) && (sum_counts >= 1) && (sum_counts < 14)) {
-        return Err(Error::from(
-            "Cannot to calculate effective tiles: invalid number of tiles.",
-        ));
-    }
-
-    // 候補牌の列挙
-    let mut candidate_tiles = HashSet::new();
-    for (t, c) in &counts {
-        if *c > 0 {
-            // 2枚以上になるときは候補
-            candidate_tiles.insert(*t);
-        }
-    }
-
-    Ok(candidate_tiles)
+    // 候補牌の列挙（2枚以上になるときは候補）
+    Ok(counts
+        .iter()
+        .filter(|(_, c)| *c > 0)
+        .map(|(t, _)| t)
+        .collect())
 }
 
 /// 国士無双手の有効牌候補を列挙
 fn listup_kokushimusou_candidate_effective_tiles(
     tiles: &[TileId],
 ) -> Result<HashSet<TileId>, Error> {
-    let counts = calculate_tile_counts(tiles);
-    let sum_counts = counts.iter().fold(0, |sum, c| sum + c.1);
-
-    // 牌数チェック（自摸した後であることを要求）
-    if !(((sum_counts % 3) == 1) && (sum_counts >= 1) && (sum_counts < 14)) {
-        return Err(Error::from(
-            "Cannot to calculate effective tiles: invalid number of tiles.",
-        ));
-    }
+    check_candidate_tiles(&TileCountArray::from_tiles(tiles))?;
 
     // 候補牌の列挙
     let candidate_tiles = HashSet::from([
@@ -243,11 +284,10 @@ fn listup_kokushimusou_candidate_effective_tiles(
 /// 通常手の有効牌（向聴数を下げる牌）を列挙
 pub fn listup_normal_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error> {
     let candidate_tiles = listup_normal_candidate_effective_tiles(tiles)?;
-    listup_effective_tiles_common(
-        tiles,
-        Vec::from_iter(candidate_tiles).as_slice(),
-        &calculate_normal_shanten,
-    )
+    let num_melds = count_implicit_melds(tiles);
+    listup_effective_tiles_common(tiles, &candidate_tiles, &|counts| {
+        calculate_normal_shanten_from_counts(counts, num_melds)
+    })
 }
 
 /// 七対子手の有効牌（向聴数を下げる牌）を列挙
@@ -255,8 +295,8 @@ pub fn listup_chitoitsu_effective_tiles(tiles: &[TileId]) -> Result<HashSet<Tile
     let candidate_tiles = listup_chitoitsu_candidate_effective_tiles(tiles)?;
     listup_effective_tiles_common(
         tiles,
-        Vec::from_iter(candidate_tiles).as_slice(),
-        &calculate_chitoitsu_shanten,
+        &candidate_tiles,
+        &calculate_chitoitsu_shanten_from_counts,
     )
 }
 
@@ -265,13 +305,18 @@ pub fn listup_kokushimusou_effective_tiles(tiles: &[TileId]) -> Result<HashSet<T
     let candidate_tiles = listup_kokushimusou_candidate_effective_tiles(tiles)?;
     listup_effective_tiles_common(
         tiles,
-        Vec::from_iter(candidate_tiles).as_slice(),
-        &calculate_kokushimusou_shanten,
+        &candidate_tiles,
+        &calculate_kokushimusou_shanten_from_counts,
     )
 }
 
 /// 有効牌（向聴数を下げる牌）を列挙
+///
+/// 13枚に満たない手牌は副露しているとみなし、通常手の有効牌のみを列挙する。
 pub fn listup_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error> {
+    if count_implicit_melds(tiles) > 0 {
+        return listup_normal_effective_tiles(tiles);
+    }
     let normal_candidates = listup_normal_candidate_effective_tiles(tiles)?;
     let chitoitsu_candidates = listup_chitoitsu_candidate_effective_tiles(tiles)?;
     let kokushimusou_candidates = listup_kokushimusou_candidate_effective_tiles(tiles)?;
@@ -281,9 +326,233 @@ pub fn listup_effective_tiles(tiles: &[TileId]) -> Result<HashSet<TileId>, Error
     marged_candidates.extend(&chitoitsu_candidates);
     marged_candidates.extend(&kokushimusou_candidates);
 
-    listup_effective_tiles_common(
-        tiles,
-        Vec::from_iter(marged_candidates).as_slice(),
-        &calculate_shanten,
-    )
+    listup_effective_tiles_common(tiles, &marged_candidates, &|counts| {
+        calculate_shanten_from_counts(counts, 0)
+    })
+}
+
+// 純手牌と副露の枚数が整合しているか確認
+fn check_hand_tiles(hand: &Hand) -> Result<(), Error> {
+    let num_tiles = hand.hand.len() + 3 * hand.melds.len();
+    if hand.hand.is_empty() || hand.melds.len() > 4 || !(13..=14).contains(&num_tiles) {
+        return Err(Error::from(
+            "Invalid hand: number of hand tiles does not match with melds.",
+        ));
+    }
+    check_tile_counts(&TileCountArray::from_tiles(&hand.marged_tiles()))
+}
+
+// 同じ牌が5枚以上ないか確認（テーブル引きの前に弾く）
+fn check_tile_counts(counts: &TileCountArray) -> Result<(), Error> {
+    if let Some((tile, found)) = counts.iter().find(|(_, c)| *c > 4) {
+        return Err(Box::new(HandError::TooManyCopies {
+            tile,
+            found: found as usize,
+        }));
+    }
+    Ok(())
+}
+
+/// 副露を含む手牌の向聴数を計算（1で一向聴, 0で聴牌, -1で和了）
+///
+/// 副露があれば七対子/国士無双は考慮しない。
+pub fn calculate_hand_shanten(hand: &Hand) -> Result<i32, Error> {
+    check_hand_tiles(hand)?;
+    let pure_tiles: Vec<TileId> = hand.hand.iter().map(|t| t.id).collect();
+    if hand.melds.is_empty() {
+        Ok(calculate_shanten(&pure_tiles))
+    } else {
+        Ok(calculate_normal_shanten(&pure_tiles))
+    }
+}
+
+/// 副露を含む手牌の有効牌（向聴数を下げる牌）を列挙
+///
+/// 副露があれば七対子/国士無双は考慮しない。
+pub fn listup_hand_effective_tiles(hand: &Hand) -> Result<HashSet<TileId>, Error> {
+    check_hand_tiles(hand)?;
+    let pure_tiles: Vec<TileId> = hand.hand.iter().map(|t| t.id).collect();
+    if hand.melds.is_empty() {
+        listup_effective_tiles(&pure_tiles)
+    } else {
+        listup_normal_effective_tiles(&pure_tiles)
+    }
+}
+
+/// 有効牌（向聴数を下げる牌）とその残り枚数を牌の種類順に列挙
+///
+/// 残り枚数は手牌（副露を含む）、表ドラ表示牌、visibleに指定した牌（河や他家の副露など）を除いて数える。
+pub fn count_effective_tiles(
+    hand: &Hand,
+    dora: &Dora,
+    visible: &[TileId],
+) -> Result<Vec<(TileId, i32)>, Error> {
+    let effective_tiles = listup_hand_effective_tiles(hand)?;
+
+    // 見えている牌を数える
+    let mut seen_tiles = hand.marged_tiles();
+    seen_tiles.extend(dora.omote.iter().map(|t| t.id));
+    seen_tiles.extend_from_slice(visible);
+    let seen_counts = TileCountArray::from_tiles(&seen_tiles);
+    if let Some((t, _)) = seen_counts.iter().find(|(_, c)| *c > 4) {
+        return Err(Error::from(format!(
+            "Cannot to count effective tiles: more than 4 tiles of {:?} are visible.",
+            t
+        )));
+    }
+
+    Ok(TileId::ALL
+        .iter()
+        .filter(|t| effective_tiles.contains(t))
+        .map(|t| (*t, 4 - seen_counts[*t] as i32))
+        .collect())
+}
+
+/// 一向聴/二向聴の手牌について、次の自摸までを考慮した受け入れの評価結果
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UkeireEvaluation {
+    /// 向聴数
+    pub shanten: i32,
+    /// 有効牌と残り枚数
+    pub effective_tiles: Vec<(TileId, i32)>,
+    /// 改良牌（向聴数は下がらないが、打牌により有効牌が増える牌）と残り枚数、改良後の有効牌の残り枚数
+    pub improving_tiles: Vec<(TileId, i32, i32)>,
+    /// 次の自摸と最善の打牌の後の有効牌の残り枚数の期待値
+    ///
+    /// 向聴数が下がった後の受け入れと現在の受け入れを区別せずに平均した値
+    pub expected_ukeire: f64,
+}
+
+/// 一向聴/二向聴の手牌（副露を含めて13枚）について、次の自摸後の受け入れを評価
+///
+/// 見えていない牌を等確率で自摸すると仮定し、自摸した牌ごとに最善の打牌をした後の
+/// 有効牌の残り枚数の期待値を計算する。有効牌を自摸した場合は向聴数が下がった後の受け入れ、
+/// 改良牌を自摸した場合は改良後の受け入れ、それ以外は自摸切りして現在の受け入れを数える。
+///
+/// 牌tの見えていない枚数をr(t)、上記の受け入れをu(t)として Σ r(t)u(t) / Σ r(t) を返す。
+/// 向聴数が下がった後の受け入れ（次の段階）と現在の受け入れ（同じ段階）を同じ重みで平均するため、
+/// 和了までの速さそのものではなく、受け入れ枚数が同じ打牌を比べるための指標として使う。
+pub fn evaluate_ukeire(
+    hand: &Hand,
+    dora: &Dora,
+    visible: &[TileId],
+) -> Result<UkeireEvaluation, Error> {
+    // 牌数チェック（槓子も3枚として数える）
+    if hand.hand.len() + 3 * hand.melds.len() != 13 {
+        return Err(Error::from(
+            "Cannot to evaluate ukeire: invalid number of tiles.",
+        ));
+    }
+    let shanten = calculate_hand_shanten(hand)?;
+    if !(1..=2).contains(&shanten) {
+        return Err(Error::from(
+            "Cannot to evaluate ukeire: hand must be 1 or 2 shanten.",
+        ));
+    }
+
+    let effective_tiles = count_effective_tiles(hand, dora, visible)?;
+    let current_ukeire: i32 = effective_tiles.iter().map(|(_, n)| n).sum();
+
+    // 見えていない牌を数える
+    let mut seen_tiles = hand.marged_tiles();
+    seen_tiles.extend(dora.omote.iter().map(|t| t.id));
+    seen_tiles.extend_from_slice(visible);
+    let seen_counts = TileCountArray::from_tiles(&seen_tiles);
+
+    let mut improving_tiles = vec![];
+    let mut num_unseen = 0;
+    let mut sum_ukeire = 0;
+    for t in TileId::ALL {
+        let remain = 4 - seen_counts[t] as i32;
+        if remain <= 0 {
+            continue;
+        }
+        // 自摸して最善の打牌をした後の受け入れ
+        let drawn = Hand {
+            hand: {
+                let mut tmp = hand.hand.clone();
+                tmp.push(Tile { id: t, aka: false });
+                tmp
+            },
+            melds: hand.melds.clone(),
+        };
+        let best = &analyze_discards(&drawn, dora, visible)?[0];
+        let ukeire = if best.shanten < shanten {
+            best.num_effective_tiles()
+        } else if best.num_effective_tiles() > current_ukeire {
+            improving_tiles.push((t, remain, best.num_effective_tiles()));
+            best.num_effective_tiles()
+        } else {
+            current_ukeire
+        };
+        num_unseen += remain;
+        sum_ukeire += remain * ukeire;
+    }
+
+    Ok(UkeireEvaluation {
+        shanten,
+        effective_tiles,
+        improving_tiles,
+        expected_ukeire: if num_unseen > 0 {
+            sum_ukeire as f64 / num_unseen as f64
+        } else {
+            0.0
+        },
+    })
+}
+
+/// 自摸後の手牌（副露を含めて14枚）について打牌候補を評価し、良い順に並べる
+///
+/// 打牌後の向聴数が小さい順、有効牌の残り枚数が多い順、有効牌の種類が多い順に並べる。
+/// 打牌した牌は河に見えているものとして残り枚数を数えるため、visibleには打牌候補の牌を含めない
+/// （含めると同じ牌を2枚として数える）。
+pub fn analyze_discards(
+    hand: &Hand,
+    dora: &Dora,
+    visible: &[TileId],
+) -> Result<Vec<DiscardCandidate>, Error> {
+    // 牌数チェック（槓子も3枚として数える）
+    if hand.hand.len() + 3 * hand.melds.len() != 14 {
+        return Err(Error::from(
+            "Cannot to analyze discards: invalid number of tiles.",
+        ));
+    }
+
+    let mut candidates: Vec<DiscardCandidate> = vec![];
+    for discard in TileId::ALL {
+        let index = match hand.hand.iter().position(|t| t.id == discard) {
+            Some(i) => i,
+            None => continue,
+        };
+        let mut remain = hand.hand.clone();
+        remain.remove(index);
+        let discarded_visible = {
+            let mut tmp = visible.to_vec();
+            tmp.push(discard);
+            tmp
+        };
+        let remain_hand = Hand {
+            hand: remain,
+            melds: hand.melds.clone(),
+        };
+        let shanten = calculate_hand_shanten(&remain_hand)?;
+        let effective_tiles = count_effective_tiles(&remain_hand, dora, &discarded_visible)?;
+        candidates.push(DiscardCandidate {
+            discard,
+            shanten,
+            effective_tiles,
+        });
+    }
+
+    // 良い順に並べる（同順位は牌の種類順）
+    candidates.sort_by_key(|c| {
+        (
+            c.shanten,
+            -c.num_effective_tiles(),
+            -(c.effective_tiles.len() as i32),
+        )
+    });
+
+    Ok(candidates)
 }
@@ -1,10 +1,17 @@
 use counter::Counter;
 use num_traits::FromPrimitive;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 /// 牌の出現カウント
 pub type TileCount = Counter<TileId, i8>;
 
-type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+/// 牌の出現カウント（固定長配列, 添字はTileId::index）
+///
+/// 向聴数計算や面子の切り分けなど、繰り返し数え直す処理で使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCountArray(pub [u8; 34]);
 
 /// 数牌の種類
 #[derive(PartialEq, Eq)]
@@ -100,7 +107,7 @@ pub struct Tile {
 }
 
 /// 風（場）
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Wind {
     /// 東
     Ton,
@@ -112,22 +119,94 @@ pub enum Wind {
     Pee,
 }
 
-/// 副露
-#[derive(Clone, Copy)]
+/// 和了牌に対する待ちの形
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wait {
+    /// 両面待ち
+    Ryanmen,
+    /// 嵌張待ち
+    Kanchan,
+    /// 辺張待ち
+    Penchan,
+    /// 双碰待ち
+    Shanpon,
+    /// 単騎待ち
+    Tanki,
+}
+
+/// 複数の待ちの形が複合した聴牌形
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WaitPattern {
+    /// 三面張（例: 23456の1-4-7待ち）
+    Sanmenchan,
+    /// 延べ単（例: 1234の1-4待ち）
+    Nobetan,
+    /// 煙突（刻子と両面塔子の複合, 例: 33345+88の3-6-8待ち）
+    Entotsu,
+}
+
+/// 副露元（鳴いた牌を捨てた席の自分から見た位置）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CalledFrom {
+    /// 上家
+    Kamicha,
+    /// 対面
+    Toimen,
+    /// 下家
+    Shimocha,
+}
+
+/// 副露（副露元fromは不明ならNone）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Meld {
     /// ポン
-    Pung { tiles: [Tile; 3] },
+    Pung {
+        tiles: [Tile; 3],
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        from: Option<CalledFrom>,
+    },
     /// チー
-    Chow { tiles: [Tile; 3] },
+    Chow {
+        tiles: [Tile; 3],
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        from: Option<CalledFrom>,
+    },
     /// 暗槓（アンカン）
     Ankan { tiles: [Tile; 4] },
     /// 大明槓（ミンカン）
-    Minkan { tiles: [Tile; 4] },
-    /// 加槓（カカン）
-    Kakan { tiles: [Tile; 4] },
+    Minkan {
+        tiles: [Tile; 4],
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        from: Option<CalledFrom>,
+    },
+    /// 加槓（カカン）: fromはポンの副露元, addedは後から加えた牌
+    Kakan {
+        tiles: [Tile; 4],
+        #[cfg_attr(
+            feature = "serde",
+            serde(default, skip_serializing_if = "Option::is_none")
+        )]
+        from: Option<CalledFrom>,
+        added: Tile,
+    },
 }
 
 /// 手牌
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand {
     /// 純手牌
     pub hand: Vec<Tile>,
@@ -136,6 +215,8 @@ pub struct Hand {
 }
 
 /// ドラ表示牌
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dora {
     /// 表ドラ
     pub omote: Vec<Tile>,
@@ -143,6 +224,125 @@ pub struct Dora {
     pub ura: Vec<Tile>,
 }
 
+/// 河に捨てられた牌
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiscardedTile {
+    /// 牌
+    pub tile: Tile,
+    /// 自摸切り？
+    pub tsumogiri: bool,
+    /// 立直宣言牌？
+    pub riichi: bool,
+    /// 鳴かれた？
+    pub called: bool,
+}
+
+/// 牌表記の解析エラー（位置は入力の先頭からの文字数）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// 予期しない文字
+    UnexpectedChar { pos: usize, c: char },
+    /// 数字の後に種類（m/p/s/z）がない
+    MissingSuit { pos: usize },
+    /// 種類の前に数字がない
+    MissingNumber { pos: usize, c: char },
+    /// 存在しない牌（8z、赤1m等）
+    NoSuchTile { pos: usize, tile: String },
+    /// 副露の括弧が閉じていない
+    UnclosedGroup { pos: usize },
+    /// 副露の牌の組み合わせが不正
+    InvalidMeld { pos: usize },
+    /// 牌の枚数が不正
+    TileCount { expected: usize, found: usize },
+    /// 予期しない語
+    UnexpectedWord { pos: usize, word: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar { pos, c } => {
+                write!(f, "unexpected character '{}' at {}", c, pos)
+            }
+            ParseError::MissingSuit { pos } => write!(f, "missing suit after number at {}", pos),
+            ParseError::MissingNumber { pos, c } => {
+                write!(f, "missing number before '{}' at {}", c, pos)
+            }
+            ParseError::NoSuchTile { pos, tile } => write!(f, "no such tile '{}' at {}", tile, pos),
+            ParseError::UnclosedGroup { pos } => write!(f, "unclosed meld group at {}", pos),
+            ParseError::InvalidMeld { pos } => write!(f, "invalid meld at {}", pos),
+            ParseError::TileCount { expected, found } => {
+                write!(f, "expected {} tile(s) but found {}", expected, found)
+            }
+            ParseError::UnexpectedWord { pos, word } => {
+                write!(f, "unexpected word '{}' at {}", word, pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 手牌の検証エラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandError {
+    /// 副露の牌の組み合わせが面子になっていない
+    InvalidMeld { tiles: Vec<Tile> },
+    /// 副露が5つ以上
+    TooManyMelds { found: usize },
+    /// 手牌の枚数（槓子は3枚と数える）が不正
+    TileCount { expected: usize, found: usize },
+    /// 同じ牌が5枚以上
+    TooManyCopies { tile: TileId, found: usize },
+    /// 同じ種類の赤5が2枚以上
+    TooManyAka { tile: TileId, found: usize },
+    /// 副露元を設定する副露がない
+    MissingMeld,
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandError::InvalidMeld { tiles } => {
+                write!(f, "invalid meld '{}'", Tile::to_compactstr(tiles))
+            }
+            HandError::TooManyMelds { found } => {
+                write!(f, "too many melds: found {} (at most 4)", found)
+            }
+            HandError::TileCount { expected, found } => write!(
+                f,
+                "expected {} tiles (counting a kan as 3) but found {}",
+                expected, found
+            ),
+            HandError::TooManyCopies { tile, found } => {
+                write!(
+                    f,
+                    "too many copies of {}: found {} (at most 4)",
+                    tile, found
+                )
+            }
+            HandError::TooManyAka { tile, found } => {
+                write!(f, "too many red {}: found {} (at most 1)", tile, found)
+            }
+            HandError::MissingMeld => write!(f, "called_from without a preceding meld"),
+        }
+    }
+}
+
+impl std::error::Error for HandError {}
+
+/// 手牌を検証しながら構築する
+///
+/// 副露は牌の並びから構成し、`build`でまとめて検証する（最初のエラーを返す）。
+#[derive(Debug, Clone, Default)]
+pub struct HandBuilder {
+    hand: Vec<Tile>,
+    melds: Vec<Meld>,
+    wining_tile: Option<Tile>,
+    error: Option<HandError>,
+}
+
 impl Hand {
     /// 門前か？
     pub fn is_menzen(&self) -> bool {
@@ -173,17 +373,153 @@ impl Hand {
         num
     }
 
+    /// 手牌を検証
+    ///
+    /// 副露の形、牌の枚数（槓子は3枚と数えて13枚または14枚、和了牌を指定すれば和了牌と合わせて14枚）、
+    /// 同じ牌が4枚以下、赤5が種類ごとに1枚以下であることを確認する。
+    pub fn validate(&self, wining_tile: Option<Tile>) -> Result<(), HandError> {
+        if self.melds.len() > 4 {
+            return Err(HandError::TooManyMelds {
+                found: self.melds.len(),
+            });
+        }
+        for m in &self.melds {
+            if !m.is_valid() {
+                return Err(HandError::InvalidMeld {
+                    tiles: m.tiles().to_vec(),
+                });
+            }
+        }
+        let num_tiles = self.hand.len() + 3 * self.melds.len() + wining_tile.iter().count();
+        let expected = if wining_tile.is_some() || num_tiles > 13 {
+            14
+        } else {
+            13
+        };
+        if num_tiles != expected {
+            return Err(HandError::TileCount {
+                expected,
+                found: num_tiles,
+            });
+        }
+        // 副露の牌（槓子の4枚目を含む）と和了牌を合わせて数える
+        let mut tiles = self.hand.clone();
+        for m in &self.melds {
+            tiles.extend_from_slice(m.tiles());
+        }
+        tiles.extend(wining_tile);
+        for id in TileId::ALL {
+            let found = tiles.iter().filter(|t| t.id == id).count();
+            if found > 4 {
+                return Err(HandError::TooManyCopies { tile: id, found });
+            }
+            let found = tiles.iter().filter(|t| t.id == id && t.aka).count();
+            if found > 1 {
+                return Err(HandError::TooManyAka { tile: id, found });
+            }
+        }
+        Ok(())
+    }
+
+    /// 簡易表記（例: 123m456p11z[<789s](5555z)）から手牌に変換
+    ///
+    /// 純手牌に続けて、`[...]`にチー/ポン/明槓、`(...)`に暗槓を記述する。
+    /// `[...]`の中には副露元の記号（`<`上家, `^`対面, `>`下家）を1つ書ける。
+    /// 加槓は最後の牌（加えた牌）の前に`+`を書く（例: `[^555+0m]`）。
+    pub fn from_compactstr(s: &str) -> Result<Self, ParseError> {
+        let mut hand = vec![];
+        let mut melds = vec![];
+        let mut concealed: Vec<(usize, char)> = vec![];
+        let mut chars_iter = s.chars().enumerate();
+        while let Some((pos, c)) = chars_iter.next() {
+            match c {
+                '[' | '(' => {
+                    let close = if c == '[' { ']' } else { ')' };
+                    let mut group: Vec<(usize, char)> = vec![];
+                    let mut from = None;
+                    let mut kakan = false;
+                    loop {
+                        match chars_iter.next() {
+                            Some((_, g)) if g == close => break,
+                            Some((gpos, g @ ('<' | '^' | '>'))) => {
+                                if c == '(' || from.is_some() {
+                                    return Err(ParseError::InvalidMeld { pos: gpos });
+                                }
+                                from = CalledFrom::from_char(g);
+                            }
+                            Some((gpos, '+')) => {
+                                if c == '(' || kakan {
+                                    return Err(ParseError::InvalidMeld { pos: gpos });
+                                }
+                                kakan = true;
+                            }
+                            Some(g) => group.push(g),
+                            None => return Err(ParseError::UnclosedGroup { pos }),
+                        }
+                    }
+                    // 副露の前までの純手牌
+                    hand.extend(parse_compact_tiles(&concealed)?);
+                    concealed.clear();
+                    let tiles = parse_compact_tiles(&group)?;
+                    let meld = if kakan {
+                        Meld::kakan_from_tiles(&tiles)
+                    } else {
+                        Meld::from_tiles(&tiles, c == '(')
+                    };
+                    let meld = meld
+                        .and_then(|m| m.with_from(from))
+                        .map_err(|_| ParseError::InvalidMeld { pos })?;
+                    melds.push(meld);
+                }
+                _ => concealed.push((pos, c)),
+            }
+        }
+        hand.extend(parse_compact_tiles(&concealed)?);
+        Ok(Hand { hand, melds })
+    }
+
+    /// 簡易表記に変換
+    pub fn to_compactstr(&self) -> String {
+        let mut s = Tile::to_compactstr(&self.hand);
+        for m in &self.melds {
+            let from = m.called_from().map(|f| f.to_char().to_string());
+            let from = from.unwrap_or_default();
+            match m {
+                Meld::Pung { tiles, .. } | Meld::Chow { tiles, .. } => {
+                    s += &format!("[{}{}]", from, Tile::to_compactstr(tiles));
+                }
+                Meld::Minkan { tiles, .. } => {
+                    s += &format!("[{}{}]", from, Tile::to_compactstr(tiles));
+                }
+                Meld::Kakan { tiles, added, .. } => {
+                    // 同じ牌4枚の簡易表記は数字4文字と種類なので、加えた牌の数字の前に+を挿入
+                    // 加えた牌はtiles中の位置によらず最後に置く
+                    let mut ts = tiles.to_vec();
+                    ts.remove(ts.iter().position(|t| t == added).unwrap_or(3));
+                    ts.push(*added);
+                    let mut k = Tile::to_compactstr(&ts);
+                    k.insert(3, '+');
+                    s += &format!("[{}{}]", from, k);
+                }
+                Meld::Ankan { tiles } => {
+                    s += &format!("({})", Tile::to_compactstr(tiles));
+                }
+            }
+        }
+        s
+    }
+
     /// 鳴いた牌を結合
     pub fn marged_tiles(&self) -> Vec<TileId> {
         let mut marged: Vec<TileId> = self.hand.iter().map(|Tile { id, .. }| *id).collect();
         for m in &self.melds {
             let ts: Vec<TileId> = match m {
-                Meld::Pung { tiles: ts } | Meld::Chow { tiles: ts } => {
+                Meld::Pung { tiles: ts, .. } | Meld::Chow { tiles: ts, .. } => {
                     ts.iter().map(|Tile { id, .. }| *id).collect()
                 }
                 Meld::Ankan { tiles: ts }
-                | Meld::Minkan { tiles: ts }
-                | Meld::Kakan { tiles: ts } => ts.iter().map(|Tile { id, .. }| *id).collect(),
+                | Meld::Minkan { tiles: ts, .. }
+                | Meld::Kakan { tiles: ts, .. } => ts.iter().map(|Tile { id, .. }| *id).collect(),
             };
             marged.extend_from_slice(ts.as_slice());
         }
@@ -191,7 +527,325 @@ impl Hand {
     }
 }
 
+impl Meld {
+    /// 牌の並びから副露を構成（4枚の場合、ankanが真なら暗槓、偽なら明槓）
+    pub fn from_tiles(tiles: &[Tile], ankan: bool) -> Result<Self, HandError> {
+        let is_same = tiles.iter().all(|t| t.id == tiles[0].id);
+        match tiles.len() {
+            3 if is_same && !ankan => Ok(Meld::Pung {
+                tiles: [tiles[0], tiles[1], tiles[2]],
+                from: None,
+            }),
+            3 if !ankan => {
+                let mut sorted = tiles.to_vec();
+                sorted.sort_by_key(|t| t.id as i32);
+                if sorted[0].id.is_suhai()
+                    && sorted[0].id.getnumber() <= 7
+                    && sorted[0].id.nth(1) == sorted[1].id
+                    && sorted[0].id.nth(2) == sorted[2].id
+                {
+                    Ok(Meld::Chow {
+                        tiles: [sorted[0], sorted[1], sorted[2]],
+                        from: None,
+                    })
+                } else {
+                    Err(HandError::InvalidMeld {
+                        tiles: tiles.to_vec(),
+                    })
+                }
+            }
+            4 if is_same => {
+                let tiles = [tiles[0], tiles[1], tiles[2], tiles[3]];
+                Ok(if ankan {
+                    Meld::Ankan { tiles }
+                } else {
+                    Meld::Minkan { tiles, from: None }
+                })
+            }
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        }
+    }
+
+    /// 同じ牌4枚から加槓を構成（最後の牌を加えた牌とする）
+    pub fn kakan_from_tiles(tiles: &[Tile]) -> Result<Self, HandError> {
+        match Meld::from_tiles(tiles, false)? {
+            Meld::Minkan { tiles, .. } => Ok(Meld::Kakan {
+                tiles,
+                from: None,
+                added: tiles[3],
+            }),
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        }
+    }
+
+    /// 副露元を設定（暗槓には設定できない）
+    pub fn with_from(self, from: Option<CalledFrom>) -> Result<Self, HandError> {
+        let mut meld = self;
+        match &mut meld {
+            Meld::Pung { from: f, .. }
+            | Meld::Chow { from: f, .. }
+            | Meld::Minkan { from: f, .. }
+            | Meld::Kakan { from: f, .. } => *f = from,
+            Meld::Ankan { tiles } if from.is_some() => {
+                return Err(HandError::InvalidMeld {
+                    tiles: tiles.to_vec(),
+                })
+            }
+            Meld::Ankan { .. } => {}
+        }
+        Ok(meld)
+    }
+
+    /// 副露を構成する牌
+    pub fn tiles(&self) -> &[Tile] {
+        match self {
+            Meld::Pung { tiles, .. } | Meld::Chow { tiles, .. } => tiles,
+            Meld::Ankan { tiles } | Meld::Minkan { tiles, .. } | Meld::Kakan { tiles, .. } => tiles,
+        }
+    }
+
+    /// 副露元（暗槓と副露元が不明な場合はNone）
+    pub fn called_from(&self) -> Option<CalledFrom> {
+        match self {
+            Meld::Pung { from, .. }
+            | Meld::Chow { from, .. }
+            | Meld::Minkan { from, .. }
+            | Meld::Kakan { from, .. } => *from,
+            Meld::Ankan { .. } => None,
+        }
+    }
+
+    // 牌の組み合わせと副露元が副露の種類に合っているか？
+    fn is_valid(&self) -> bool {
+        let tiles = self.tiles();
+        match self {
+            // チーは上家からのみ
+            Meld::Chow { from, .. } => {
+                matches!(Meld::from_tiles(tiles, false), Ok(Meld::Chow { .. }))
+                    && matches!(from, None | Some(CalledFrom::Kamicha))
+            }
+            Meld::Kakan { added, .. } => {
+                tiles.iter().all(|t| t.id == tiles[0].id) && tiles.contains(added)
+            }
+            _ => tiles.iter().all(|t| t.id == tiles[0].id),
+        }
+    }
+}
+
+impl HandBuilder {
+    /// 空の手牌から構築を開始
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 純手牌を追加
+    pub fn tiles(mut self, tiles: &[Tile]) -> Self {
+        self.hand.extend_from_slice(tiles);
+        self
+    }
+
+    /// 副露を追加
+    pub fn meld(mut self, meld: Meld) -> Self {
+        self.melds.push(meld);
+        self
+    }
+
+    /// チーを追加
+    pub fn chow(self, tiles: &[Tile]) -> Self {
+        let meld = match Meld::from_tiles(tiles, false) {
+            Ok(m @ Meld::Chow { .. }) => Ok(m),
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        };
+        self.push_meld(meld)
+    }
+
+    /// ポンを追加
+    pub fn pung(self, tiles: &[Tile]) -> Self {
+        let meld = match Meld::from_tiles(tiles, false) {
+            Ok(m @ Meld::Pung { .. }) => Ok(m),
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        };
+        self.push_meld(meld)
+    }
+
+    /// 暗槓を追加
+    pub fn ankan(self, tiles: &[Tile]) -> Self {
+        let meld = match Meld::from_tiles(tiles, true) {
+            Ok(m @ Meld::Ankan { .. }) => Ok(m),
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        };
+        self.push_meld(meld)
+    }
+
+    /// 大明槓を追加
+    pub fn minkan(self, tiles: &[Tile]) -> Self {
+        let meld = match Meld::from_tiles(tiles, false) {
+            Ok(m @ Meld::Minkan { .. }) => Ok(m),
+            _ => Err(HandError::InvalidMeld {
+                tiles: tiles.to_vec(),
+            }),
+        };
+        self.push_meld(meld)
+    }
+
+    /// 加槓を追加（最後の牌を加えた牌とする）
+    pub fn kakan(self, tiles: &[Tile]) -> Self {
+        let meld = Meld::kakan_from_tiles(tiles);
+        self.push_meld(meld)
+    }
+
+    /// 直前に追加した副露の副露元を設定
+    pub fn called_from(mut self, from: CalledFrom) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        let meld = match self.melds.pop() {
+            Some(m) => m.with_from(Some(from)),
+            None => Err(HandError::MissingMeld),
+        };
+        self.push_meld(meld)
+    }
+
+    /// 和了牌を指定（検証にのみ使い、手牌には含めない）
+    pub fn wining_tile(mut self, tile: Tile) -> Self {
+        self.wining_tile = Some(tile);
+        self
+    }
+
+    /// 手牌を検証して構築
+    pub fn build(self) -> Result<Hand, HandError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let hand = Hand {
+            hand: self.hand,
+            melds: self.melds,
+        };
+        hand.validate(self.wining_tile)?;
+        Ok(hand)
+    }
+
+    // 副露を追加（構成に失敗していれば最初のエラーを記録）
+    fn push_meld(mut self, meld: Result<Meld, HandError>) -> Self {
+        match meld {
+            Ok(m) => self.melds.push(m),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+}
+
+impl CalledFrom {
+    /// 簡易表記の記号（`<`上家, `^`対面, `>`下家）に変換
+    pub fn to_char(&self) -> char {
+        match self {
+            CalledFrom::Kamicha => '<',
+            CalledFrom::Toimen => '^',
+            CalledFrom::Shimocha => '>',
+        }
+    }
+
+    /// 簡易表記の記号から変換
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '<' => Some(CalledFrom::Kamicha),
+            '^' => Some(CalledFrom::Toimen),
+            '>' => Some(CalledFrom::Shimocha),
+            _ => None,
+        }
+    }
+}
+
+impl Wait {
+    /// 日本語文字列に変換
+    pub fn to_jpstr(&self) -> &str {
+        match self {
+            Wait::Ryanmen => "両面",
+            Wait::Kanchan => "嵌張",
+            Wait::Penchan => "辺張",
+            Wait::Shanpon => "双碰",
+            Wait::Tanki => "単騎",
+        }
+    }
+}
+
+impl WaitPattern {
+    /// 日本語文字列に変換
+    pub fn to_jpstr(&self) -> &str {
+        match self {
+            WaitPattern::Sanmenchan => "三面張",
+            WaitPattern::Nobetan => "延べ単",
+            WaitPattern::Entotsu => "煙突",
+        }
+    }
+}
+
 impl TileId {
+    /// 全ての牌の種類（萬子/筒子/索子/字牌の順）
+    pub const ALL: [TileId; 34] = [
+        TileId::Id1man,
+        TileId::Id2man,
+        TileId::Id3man,
+        TileId::Id4man,
+        TileId::Id5man,
+        TileId::Id6man,
+        TileId::Id7man,
+        TileId::Id8man,
+        TileId::Id9man,
+        TileId::Id1pin,
+        TileId::Id2pin,
+        TileId::Id3pin,
+        TileId::Id4pin,
+        TileId::Id5pin,
+        TileId::Id6pin,
+        TileId::Id7pin,
+        TileId::Id8pin,
+        TileId::Id9pin,
+        TileId::Id1sou,
+        TileId::Id2sou,
+        TileId::Id3sou,
+        TileId::Id4sou,
+        TileId::Id5sou,
+        TileId::Id6sou,
+        TileId::Id7sou,
+        TileId::Id8sou,
+        TileId::Id9sou,
+        TileId::IdTon,
+        TileId::IdNan,
+        TileId::IdSha,
+        TileId::IdPee,
+        TileId::IdHaku,
+        TileId::IdHatu,
+        TileId::IdChun,
+    ];
+
+    /// 萬子/筒子/索子/字牌の順に並べたときの位置（0から33）
+    pub fn index(&self) -> usize {
+        let id = *self as usize;
+        if id < 30 {
+            (id / 10) * 9 + id % 10 - 1
+        } else {
+            27 + id - 31
+        }
+    }
+
+    /// 位置（0から33）から識別子を取得
+    pub fn from_index(index: usize) -> Self {
+        TileId::ALL[index]
+    }
+
     /// 識別子の相対位置
     pub fn nth(&self, index: i32) -> Self {
         FromPrimitive::from_i32(*self as i32 + index).unwrap()
@@ -249,7 +903,7 @@ impl TileId {
     }
 
     /// 文字を識別子に変換
-    pub fn from_char(c: char) -> Result<Self, Error> {
+    pub fn from_char(c: char) -> Result<Self, ParseError> {
         let ret = match c {
             '\u{1f000}' => TileId::IdTon,
             '\u{1f001}' => TileId::IdNan,
@@ -286,38 +940,133 @@ impl TileId {
             '\u{1f020}' => TileId::Id8pin,
             '\u{1f021}' => TileId::Id9pin,
             _ => {
-                return Err(Error::from("invalid Mahjong char"));
+                return Err(ParseError::UnexpectedChar { pos: 0, c });
             }
         };
         Ok(ret)
     }
 
     /// 牌文字列を識別子に変換
-    pub fn from_tilestr(s: &str) -> Result<Vec<Self>, Error> {
+    pub fn from_tilestr(s: &str) -> Result<Vec<Self>, ParseError> {
         let mut tiles = vec![];
-        for c in s.chars() {
-            tiles.push(TileId::from_char(c)?);
+        for (pos, c) in s.chars().enumerate() {
+            tiles.push(TileId::from_char(c).map_err(|_| ParseError::UnexpectedChar { pos, c })?);
         }
         Ok(tiles)
     }
+
+    // 字牌の漢字を識別子に変換
+    fn from_jihai_char(c: char) -> Option<Self> {
+        match c {
+            '東' => Some(TileId::IdTon),
+            '南' => Some(TileId::IdNan),
+            '西' => Some(TileId::IdSha),
+            '北' => Some(TileId::IdPee),
+            '白' => Some(TileId::IdHaku),
+            '発' => Some(TileId::IdHatu),
+            '中' => Some(TileId::IdChun),
+            _ => None,
+        }
+    }
+
+    /// 識別子を文字に変換
+    pub fn to_char(&self) -> char {
+        match self {
+            TileId::IdTon => '\u{1f000}',
+            TileId::IdNan => '\u{1f001}',
+            TileId::IdSha => '\u{1f002}',
+            TileId::IdPee => '\u{1f003}',
+            TileId::IdChun => '\u{1f004}',
+            TileId::IdHatu => '\u{1f005}',
+            TileId::IdHaku => '\u{1f006}',
+            TileId::Id1man => '\u{1f007}',
+            TileId::Id2man => '\u{1f008}',
+            TileId::Id3man => '\u{1f009}',
+            TileId::Id4man => '\u{1f00a}',
+            TileId::Id5man => '\u{1f00b}',
+            TileId::Id6man => '\u{1f00c}',
+            TileId::Id7man => '\u{1f00d}',
+            TileId::Id8man => '\u{1f00e}',
+            TileId::Id9man => '\u{1f00f}',
+            TileId::Id1sou => '\u{1f010}',
+            TileId::Id2sou => '\u{1f011}',
+            TileId::Id3sou => '\u{1f012}',
+            TileId::Id4sou => '\u{1f013}',
+            TileId::Id5sou => '\u{1f014}',
+            TileId::Id6sou => '\u{1f015}',
+            TileId::Id7sou => '\u{1f016}',
+            TileId::Id8sou => '\u{1f017}',
+            TileId::Id9sou => '\u{1f018}',
+            TileId::Id1pin => '\u{1f019}',
+            TileId::Id2pin => '\u{1f01a}',
+            TileId::Id3pin => '\u{1f01b}',
+            TileId::Id4pin => '\u{1f01c}',
+            TileId::Id5pin => '\u{1f01d}',
+            TileId::Id6pin => '\u{1f01e}',
+            TileId::Id7pin => '\u{1f01f}',
+            TileId::Id8pin => '\u{1f020}',
+            TileId::Id9pin => '\u{1f021}',
+        }
+    }
+
+    /// 識別子を牌文字列に変換
+    pub fn to_tilestr(tiles: &[TileId]) -> String {
+        tiles.iter().map(|t| t.to_char()).collect()
+    }
 }
 
 impl Tile {
+    /// 簡易表記（例: 123m406p11z）から牌に変換
+    ///
+    /// 数字の後に種類（m/p/s/z）を続ける。0は赤5、1z〜7zは東南西北白発中を表す。
+    /// mjscoreの牌表示（大文字の種類で赤5、漢字の字牌）も受け付ける。
+    pub fn from_compactstr(s: &str) -> Result<Vec<Self>, ParseError> {
+        parse_compact_tiles(&s.chars().enumerate().collect::<Vec<(usize, char)>>())
+    }
+
+    /// 簡易表記に変換（同じ種類が続く数字をまとめる）
+    pub fn to_compactstr(tiles: &[Tile]) -> String {
+        let mut s = String::new();
+        let mut suit: Option<char> = None;
+        for t in tiles {
+            let (number, ty) = if t.id.is_suhai() {
+                let ty = match t.id.gettype() {
+                    TileType::MANZU => 'm',
+                    TileType::PINZU => 'p',
+                    TileType::SOUZU => 's',
+                };
+                (if t.aka { 0 } else { t.id.getnumber() }, ty)
+            } else {
+                (t.id as i32 - TileId::IdTon as i32 + 1, 'z')
+            };
+            match suit {
+                Some(prev) if prev != ty => s.push(prev),
+                _ => {}
+            }
+            s += &number.to_string();
+            suit = Some(ty);
+        }
+        if let Some(ty) = suit {
+            s.push(ty);
+        }
+        s
+    }
+
     /// mjscoreの牌表示から牌に変換
-    pub fn from_mjscorestr(s: &str) -> Result<Vec<Self>, Error> {
+    pub fn from_mjscorestr(s: &str) -> Result<Vec<Self>, ParseError> {
         let mut tiles = vec![];
-        let mut chars_iter = s.chars();
-        while let Some(c) = chars_iter.next() {
+        let mut chars_iter = s.chars().enumerate();
+        while let Some((pos, c)) = chars_iter.next() {
             match c {
                 '1'..='9' => {
-                    let ty = chars_iter.next().unwrap();
+                    let (ty_pos, ty) = chars_iter.next().ok_or(ParseError::MissingSuit { pos })?;
                     let offset = c.to_digit(10).unwrap() as i32 - 1;
                     let id = match ty.to_ascii_lowercase() {
                         'm' => TileId::Id1man.nth(offset),
                         'p' => TileId::Id1pin.nth(offset),
                         's' => TileId::Id1sou.nth(offset),
                         _ => {
-                            return Err(Error::from("invalid mjscore hai char"));
+                            return Err(ParseError::UnexpectedChar { pos: ty_pos, c: ty });
                         }
                     };
                     tiles.push(Tile {
@@ -325,50 +1074,10 @@ impl Tile {
                         aka: ty.is_uppercase(),
                     });
                 }
-                '東' => {
-                    tiles.push(Tile {
-                        id: TileId::IdTon,
-                        aka: false,
-                    });
-                }
-                '南' => {
-                    tiles.push(Tile {
-                        id: TileId::IdNan,
-                        aka: false,
-                    });
-                }
-                '西' => {
-                    tiles.push(Tile {
-                        id: TileId::IdSha,
-                        aka: false,
-                    });
-                }
-                '北' => {
-                    tiles.push(Tile {
-                        id: TileId::IdPee,
-                        aka: false,
-                    });
-                }
-                '白' => {
-                    tiles.push(Tile {
-                        id: TileId::IdHaku,
-                        aka: false,
-                    });
-                }
-                '発' => {
-                    tiles.push(Tile {
-                        id: TileId::IdHatu,
-                        aka: false,
-                    });
-                }
-                '中' => {
-                    tiles.push(Tile {
-                        id: TileId::IdChun,
-                        aka: false,
-                    });
-                }
                 _ => {
-                    return Err(Error::from("invalid mjscore hai char"));
+                    let id =
+                        TileId::from_jihai_char(c).ok_or(ParseError::UnexpectedChar { pos, c })?;
+                    tiles.push(Tile { id, aka: false });
                 }
             }
         }
@@ -422,7 +1131,330 @@ impl Tile {
     }
 }
 
+// 位置付きの文字列を簡易表記として牌に変換
+fn parse_compact_tiles(chars: &[(usize, char)]) -> Result<Vec<Tile>, ParseError> {
+    let mut tiles = vec![];
+    let mut digits: Vec<(usize, u32)> = vec![];
+    for &(pos, c) in chars {
+        match c {
+            '0'..='9' => {
+                digits.push((pos, c.to_digit(10).unwrap()));
+            }
+            'm' | 'p' | 's' | 'M' | 'P' | 'S' => {
+                if digits.is_empty() {
+                    return Err(ParseError::MissingNumber { pos, c });
+                }
+                let first = match c.to_ascii_lowercase() {
+                    'm' => TileId::Id1man,
+                    'p' => TileId::Id1pin,
+                    _ => TileId::Id1sou,
+                };
+                for (dpos, d) in digits.drain(..) {
+                    // 大文字はmjscoreの赤5
+                    if c.is_uppercase() && d != 5 && d != 0 {
+                        return Err(ParseError::NoSuchTile {
+                            pos: dpos,
+                            tile: format!("{}{}", d, c),
+                        });
+                    }
+                    let number = if d == 0 { 5 } else { d as i32 };
+                    tiles.push(Tile {
+                        id: first.nth(number - 1),
+                        aka: d == 0 || c.is_uppercase(),
+                    });
+                }
+            }
+            'z' => {
+                if digits.is_empty() {
+                    return Err(ParseError::MissingNumber { pos, c });
+                }
+                for (dpos, d) in digits.drain(..) {
+                    if !(1..=7).contains(&d) {
+                        return Err(ParseError::NoSuchTile {
+                            pos: dpos,
+                            tile: format!("{}z", d),
+                        });
+                    }
+                    tiles.push(Tile {
+                        id: TileId::IdTon.nth(d as i32 - 1),
+                        aka: false,
+                    });
+                }
+            }
+            _ => {
+                let id = TileId::from_jihai_char(c).ok_or(ParseError::UnexpectedChar { pos, c })?;
+                if let Some((dpos, _)) = digits.first() {
+                    return Err(ParseError::MissingSuit { pos: *dpos });
+                }
+                tiles.push(Tile { id, aka: false });
+            }
+        }
+    }
+    if let Some((dpos, _)) = digits.first() {
+        return Err(ParseError::MissingSuit { pos: *dpos });
+    }
+    Ok(tiles)
+}
+
+// 1枚だけの牌表記を変換
+fn parse_single_tile(s: &str) -> Result<Tile, ParseError> {
+    let tiles = Tile::from_compactstr(s)?;
+    if tiles.len() != 1 {
+        return Err(ParseError::TileCount {
+            expected: 1,
+            found: tiles.len(),
+        });
+    }
+    Ok(tiles[0])
+}
+
+impl fmt::Display for TileId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            Tile {
+                id: *self,
+                aka: false
+            }
+        )
+    }
+}
+
+impl FromStr for TileId {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_single_tile(s)?.id)
+    }
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Tile::to_compactstr(&[*self]))
+    }
+}
+
+impl FromStr for Tile {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_single_tile(s)
+    }
+}
+
+impl fmt::Display for Meld {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hand = Hand {
+            hand: vec![],
+            melds: vec![*self],
+        };
+        write!(f, "{}", hand.to_compactstr())
+    }
+}
+
+impl FromStr for Meld {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hand = Hand::from_compactstr(s)?;
+        if !hand.hand.is_empty() || hand.melds.len() != 1 {
+            return Err(ParseError::InvalidMeld { pos: 0 });
+        }
+        Ok(hand.melds[0])
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_compactstr())
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Hand::from_compactstr(s)
+    }
+}
+
+impl fmt::Display for Dora {
+    /// 表ドラ表示牌に続けて、裏ドラ表示牌があれば`/`で区切って表示
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Tile::to_compactstr(&self.omote))?;
+        if !self.ura.is_empty() {
+            write!(f, "/{}", Tile::to_compactstr(&self.ura))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Dora {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<(usize, char)> = s.chars().enumerate().collect();
+        match chars.iter().position(|(_, c)| *c == '/') {
+            Some(i) => Ok(Dora {
+                omote: parse_compact_tiles(&chars[..i])?,
+                ura: parse_compact_tiles(&chars[i + 1..])?,
+            }),
+            None => Ok(Dora {
+                omote: parse_compact_tiles(&chars)?,
+                ura: vec![],
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Wind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Wind::Ton => "ton",
+            Wind::Nan => "nan",
+            Wind::Sha => "sha",
+            Wind::Pee => "pee",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Wind {
+    type Err = ParseError;
+
+    /// ton/nan/sha/pee（大文字小文字は区別しない）または東/南/西/北から変換
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ton" | "東" => Ok(Wind::Ton),
+            "nan" | "南" => Ok(Wind::Nan),
+            "sha" | "西" => Ok(Wind::Sha),
+            "pee" | "北" => Ok(Wind::Pee),
+            _ => Err(ParseError::UnexpectedWord {
+                pos: 0,
+                word: s.to_string(),
+            }),
+        }
+    }
+}
+
+// 文字列表記（Display/FromStr）でシリアライズする型のserde実装
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_as_str {
+    ($($t:ty),*) => {
+        $(
+            impl serde::Serialize for $t {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $t {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+                    s.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde_as_str!(TileId, Tile, Wind);
+
 /// 牌の出現回数を計算
 pub fn calculate_tile_counts(tiles: &[TileId]) -> TileCount {
     tiles.iter().cloned().collect()
 }
+
+impl TileCountArray {
+    /// 全て0枚のカウントを作成
+    pub fn new() -> Self {
+        TileCountArray([0; 34])
+    }
+
+    /// 牌の並びから出現回数を計算
+    pub fn from_tiles(tiles: &[TileId]) -> Self {
+        let mut counts = Self::new();
+        for t in tiles {
+            counts[*t] += 1;
+        }
+        counts
+    }
+
+    /// 牌と出現回数の組を牌の種類順に列挙（0枚の牌も含む）
+    pub fn iter(&self) -> impl Iterator<Item = (TileId, u8)> + '_ {
+        TileId::ALL.iter().map(move |t| (*t, self[*t]))
+    }
+
+    /// 牌の総数
+    pub fn sum(&self) -> u32 {
+        self.0.iter().map(|c| *c as u32).sum()
+    }
+
+    /// 数牌の種類ごとの出現回数（一から九の順）
+    pub fn suhai(&self, ty: TileType) -> &[u8] {
+        match ty {
+            TileType::MANZU => &self.0[0..9],
+            TileType::PINZU => &self.0[9..18],
+            TileType::SOUZU => &self.0[18..27],
+        }
+    }
+
+    /// 字牌の出現回数（東南西北白發中の順）
+    pub fn jihai(&self) -> &[u8] {
+        &self.0[27..34]
+    }
+}
+
+impl Default for TileCountArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Index<TileId> for TileCountArray {
+    type Output = u8;
+
+    fn index(&self, t: TileId) -> &u8 {
+        &self.0[t.index()]
+    }
+}
+
+impl IndexMut<TileId> for TileCountArray {
+    fn index_mut(&mut self, t: TileId) -> &mut u8 {
+        &mut self.0[t.index()]
+    }
+}
+
+impl From<&[TileId]> for TileCountArray {
+    fn from(tiles: &[TileId]) -> Self {
+        Self::from_tiles(tiles)
+    }
+}
+
+impl From<Vec<TileId>> for TileCountArray {
+    fn from(tiles: Vec<TileId>) -> Self {
+        Self::from_tiles(&tiles)
+    }
+}
+
+impl From<&TileCount> for TileCountArray {
+    fn from(counts: &TileCount) -> Self {
+        let mut array = Self::new();
+        for (t, c) in counts.iter() {
+            array[*t] = (*c).max(0) as u8;
+        }
+        array
+    }
+}
+
+impl From<&TileCountArray> for TileCount {
+    fn from(counts: &TileCountArray) -> Self {
+        counts
+            .iter()
+            .filter(|(_, c)| *c > 0)
+            .map(|(t, c)| (t, c as i8))
+            .collect()
+    }
+}
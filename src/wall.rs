@@ -0,0 +1,265 @@
+use crate::types::*;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// 牌の総数
+pub const NUM_WALL_TILES: usize = 136;
+/// 王牌の枚数
+pub const NUM_DEAD_WALL_TILES: usize = 14;
+/// 嶺上牌の枚数（槓の最大回数）
+pub const NUM_RINSHAN_TILES: usize = 4;
+/// ドラ表示牌の最大枚数
+pub const NUM_MAX_DORA: usize = 5;
+
+/// 牌山の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WallConfig {
+    /// 赤五萬の枚数
+    pub aka_man: usize,
+    /// 赤五筒の枚数
+    pub aka_pin: usize,
+    /// 赤五索の枚数
+    pub aka_sou: usize,
+}
+
+/// 牌山
+///
+/// 先頭から自摸し、末尾14枚を王牌とする。王牌は先頭4枚が嶺上牌、
+/// 続く10枚がドラ表示牌と裏ドラ表示牌の組（表, 裏, 表, 裏, ...）の並び。
+/// 嶺上牌を自摸した分だけ海底牌が繰り上がる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "WallData"))]
+pub struct Wall {
+    /// 全ての牌（自摸順）
+    tiles: Vec<Tile>,
+    /// 自摸した牌の数（嶺上牌を除く）
+    num_drawn: usize,
+    /// 自摸した嶺上牌の数
+    num_rinshan: usize,
+    /// めくられたドラ表示牌の数
+    num_dora: usize,
+}
+
+// デシリアライズ時に検証してから牌山にするための中間表現
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct WallData {
+    tiles: Vec<Tile>,
+    num_drawn: usize,
+    num_rinshan: usize,
+    num_dora: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<WallData> for Wall {
+    type Error = Error;
+
+    fn try_from(data: WallData) -> Result<Self, Error> {
+        let mut wall = Wall::from_tiles(data.tiles)?;
+        if data.num_rinshan > NUM_RINSHAN_TILES {
+            return Err(Error::from(format!(
+                "Invalid wall: number of rinshan draws must be at most {}.",
+                NUM_RINSHAN_TILES
+            )));
+        }
+        if data.num_drawn + data.num_rinshan > NUM_WALL_TILES - NUM_DEAD_WALL_TILES {
+            return Err(Error::from(format!(
+                "Invalid wall: number of drawn tiles must be at most {}.",
+                NUM_WALL_TILES - NUM_DEAD_WALL_TILES
+            )));
+        }
+        if data.num_dora == 0 || data.num_dora > NUM_MAX_DORA {
+            return Err(Error::from(format!(
+                "Invalid wall: number of dora indicators must be 1 to {}.",
+                NUM_MAX_DORA
+            )));
+        }
+        wall.num_drawn = data.num_drawn;
+        wall.num_rinshan = data.num_rinshan;
+        wall.num_dora = data.num_dora;
+        Ok(wall)
+    }
+}
+
+impl Default for WallConfig {
+    fn default() -> Self {
+        WallConfig {
+            aka_man: 1,
+            aka_pin: 1,
+            aka_sou: 1,
+        }
+    }
+}
+
+impl WallConfig {
+    /// 設定に従った136枚の牌を牌の種類順に列挙
+    pub fn all_tiles(&self) -> Result<Vec<Tile>, Error> {
+        if [self.aka_man, self.aka_pin, self.aka_sou]
+            .iter()
+            .any(|n| *n > 4)
+        {
+            return Err(Error::from(
+                "Invalid wall config: number of red fives must be at most 4.",
+            ));
+        }
+        let mut tiles = Vec::with_capacity(NUM_WALL_TILES);
+        for id in TileId::ALL {
+            let num_aka = match id {
+                TileId::Id5man => self.aka_man,
+                TileId::Id5pin => self.aka_pin,
+                TileId::Id5sou => self.aka_sou,
+                _ => 0,
+            };
+            for i in 0..4 {
+                tiles.push(Tile {
+                    id,
+                    aka: i < num_aka,
+                });
+            }
+        }
+        Ok(tiles)
+    }
+}
+
+impl Wall {
+    /// シードを指定して牌山を積む（同じシードなら同じ並びになる）
+    pub fn new(config: &WallConfig, seed: u64) -> Result<Self, Error> {
+        let mut tiles = config.all_tiles()?;
+        tiles.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+        Self::from_tiles(tiles)
+    }
+
+    /// 並びを指定して牌山を積む
+    pub fn from_tiles(tiles: Vec<Tile>) -> Result<Self, Error> {
+        if tiles.len() != NUM_WALL_TILES {
+            return Err(Error::from(format!(
+                "Invalid wall: expected {} tiles but got {}.",
+                NUM_WALL_TILES,
+                tiles.len()
+            )));
+        }
+        let counts = TileCountArray::from_tiles(&tiles.iter().map(|t| t.id).collect::<Vec<_>>());
+        if let Some((t, _)) = counts.iter().find(|(_, c)| *c != 4) {
+            return Err(Error::from(format!(
+                "Invalid wall: number of {:?} is not 4.",
+                t
+            )));
+        }
+        if let Some(t) = tiles
+            .iter()
+            .find(|t| t.aka && !(t.id.is_suhai() && t.id.getnumber() == 5))
+        {
+            return Err(Error::from(format!(
+                "Invalid wall: {:?} cannot be a red tile.",
+                t.id
+            )));
+        }
+        Ok(Wall {
+            tiles,
+            num_drawn: 0,
+            num_rinshan: 0,
+            num_dora: 1,
+        })
+    }
+
+    /// 全ての牌（自摸順）
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    /// 王牌
+    pub fn dead_wall(&self) -> &[Tile] {
+        &self.tiles[NUM_WALL_TILES - NUM_DEAD_WALL_TILES..]
+    }
+
+    /// 自摸できる残り枚数
+    pub fn num_remaining(&self) -> usize {
+        NUM_WALL_TILES - NUM_DEAD_WALL_TILES - self.num_drawn - self.num_rinshan
+    }
+
+    /// 自摸した嶺上牌の数
+    pub fn num_rinshan(&self) -> usize {
+        self.num_rinshan
+    }
+
+    /// 海底牌を自摸した後か？
+    pub fn is_haitei(&self) -> bool {
+        self.num_remaining() == 0
+    }
+
+    /// 配牌（親から順に4枚ずつ3巡, 最後に1枚ずつ）
+    ///
+    /// 配牌の前に自摸していればエラー。添字0が親の手牌。
+    pub fn deal(&mut self) -> Result<[Vec<Tile>; 4], Error> {
+        if self.num_drawn != 0 || self.num_rinshan != 0 {
+            return Err(Error::from("Cannot to deal: tiles already drawn."));
+        }
+        let mut hands: [Vec<Tile>; 4] = Default::default();
+        for _ in 0..3 {
+            for hand in hands.iter_mut() {
+                hand.extend_from_slice(&self.tiles[self.num_drawn..self.num_drawn + 4]);
+                self.num_drawn += 4;
+            }
+        }
+        for hand in hands.iter_mut() {
+            hand.push(self.tiles[self.num_drawn]);
+            self.num_drawn += 1;
+        }
+        Ok(hands)
+    }
+
+    /// 自摸（残りがなければNone）
+    pub fn draw(&mut self) -> Option<Tile> {
+        if self.num_remaining() == 0 {
+            return None;
+        }
+        let tile = self.tiles[self.num_drawn];
+        self.num_drawn += 1;
+        Some(tile)
+    }
+
+    /// 嶺上牌の自摸（嶺上牌が尽きたか、自摸できる牌がなければNone）
+    ///
+    /// 海底牌が1枚繰り上がる。新ドラはflip_doraでめくる。
+    pub fn draw_rinshan(&mut self) -> Option<Tile> {
+        if self.num_rinshan >= NUM_RINSHAN_TILES || self.num_remaining() == 0 {
+            return None;
+        }
+        let tile = self.dead_wall()[self.num_rinshan];
+        self.num_rinshan += 1;
+        Some(tile)
+    }
+
+    /// 槓ドラをめくる（めくった表示牌, 全てめくられていればNone）
+    pub fn flip_dora(&mut self) -> Option<Tile> {
+        if self.num_dora >= NUM_MAX_DORA {
+            return None;
+        }
+        self.num_dora += 1;
+        Some(self.dead_wall()[NUM_RINSHAN_TILES + 2 * (self.num_dora - 1)])
+    }
+
+    /// めくられたドラ表示牌の数
+    pub fn num_dora(&self) -> usize {
+        self.num_dora
+    }
+
+    /// ドラ表示牌（with_uraがfalseなら裏ドラは空）
+    pub fn dora(&self, with_ura: bool) -> Dora {
+        let indicators = &self.dead_wall()[NUM_RINSHAN_TILES..];
+        Dora {
+            omote: (0..self.num_dora).map(|i| indicators[2 * i]).collect(),
+            ura: if with_ura {
+                (0..self.num_dora).map(|i| indicators[2 * i + 1]).collect()
+            } else {
+                vec![]
+            },
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// 複数のテストで使う点数のフィクスチャ（使わないテストもあるため未使用を許す）
+#![allow(dead_code)]
+
+use mjtools::score::*;
+
+// 放銃時に支払う点数がpointの得点計算結果
+pub fn ron(point: i32) -> Score {
+    Score {
+        han: 1,
+        fu: 30,
+        fu_detail: vec![],
+        yaku: vec![],
+        point: Point {
+            get: point,
+            feed: Feed::Duck { point },
+        },
+    }
+}
+
+// 自摸で子がko, 親がoyaを支払う得点計算結果
+pub fn tsumo(ko: i32, oya: i32) -> Score {
+    Score {
+        han: 1,
+        fu: 30,
+        fu_detail: vec![],
+        yaku: vec![],
+        point: Point {
+            get: 2 * ko + oya,
+            feed: Feed::Tsumo { ko, oya },
+        },
+    }
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use common::*;
+use mjtools::game::*;
+use mjtools::kyoku::*;
+use mjtools::types::*;
+
+const NO_RIICHI: [bool; 4] = [false; 4];
+
+#[test]
+fn test_game_renchan_and_honba() {
+    let mut game = Game::new(&GameConfig::default());
+    assert_eq!(game.scores(), [25000; 4]);
+
+    // 親の和了で連荘
+    let deltas = game
+        .apply_agari(0, Some(1), &ron(12000), NO_RIICHI)
+        .unwrap();
+    assert_eq!(deltas, [12000, -12000, 0, 0]);
+    assert_eq!(
+        (game.round(), game.dealer(), game.nhonba()),
+        (Wind::Ton, 0, 1)
+    );
+
+    // 親が不聴の流局で親流れ、本場は積まれ立直棒は供託、不聴罰符を精算
+    let deltas = game
+        .apply_ryukyoku([false, true, false, false], [false, true, false, false])
+        .unwrap();
+    assert_eq!(deltas, [-1000, 3000 - 1000, -1000, -1000]);
+    assert_eq!((game.dealer(), game.nhonba(), game.nriichi()), (1, 2, 1));
+
+    // 親が聴牌の流局で連荘
+    let deltas = game
+        .apply_ryukyoku([false, true, false, false], NO_RIICHI)
+        .unwrap();
+    assert_eq!(deltas, [-1000, 3000, -1000, -1000]);
+    assert_eq!((game.dealer(), game.nhonba()), (1, 3));
+
+    // 途中流局は点数の移動なしで連荘
+    let deltas = game.apply_abortive_draw(NO_RIICHI).unwrap();
+    assert_eq!(deltas, [0; 4]);
+    assert_eq!((game.dealer(), game.nhonba()), (1, 4));
+
+    // 子の自摸で供託を受け取り、親流れ
+    let deltas = game
+        .apply_agari(2, None, &tsumo(1300, 2300), NO_RIICHI)
+        .unwrap();
+    assert_eq!(deltas, [-1300, -2300, 1300 + 1300 + 2300 + 1000, -1300]);
+    assert_eq!((game.dealer(), game.nhonba(), game.nriichi()), (2, 0, 0));
+    assert_eq!(game.scores().iter().sum::<i32>(), 100000);
+
+    // 支払いの形が和了の形と合わない
+    assert!(game.apply_agari(1, None, &ron(1000), NO_RIICHI).is_err());
+    assert!(game.apply_agari(1, Some(1), &ron(1000), NO_RIICHI).is_err());
+}
+
+#[test]
+fn test_game_multiple_ron() {
+    let mut game = Game::new(&GameConfig::default());
+    game.apply_ryukyoku([false; 4], [false, false, false, true])
+        .unwrap();
+    assert_eq!((game.dealer(), game.nhonba(), game.nriichi()), (1, 1, 1));
+
+    // 本場と供託は放銃者の下家から最も近い和了者、親が和了者に含まれれば連荘
+    let deltas = game
+        .apply_multiple_ron(
+            2,
+            &[(3, &ron(2000 + 300)), (1, &ron(5800 + 300))],
+            [false, true, false, false],
+        )
+        .unwrap();
+    assert_eq!(deltas, [0, 5800 - 1000, -(2300 + 5800), 2300 + 2000]);
+    assert_eq!((game.dealer(), game.nhonba(), game.nriichi()), (1, 2, 0));
+    assert_eq!(game.scores().iter().sum::<i32>(), 100000);
+
+    // 和了者の重複, 放銃者の和了, 自摸
+    assert!(game
+        .apply_multiple_ron(2, &[(1, &ron(1000)), (1, &ron(1000))], NO_RIICHI)
+        .is_err());
+    assert!(game
+        .apply_multiple_ron(2, &[(2, &ron(1000))], NO_RIICHI)
+        .is_err());
+    assert!(game
+        .apply_multiple_ron(2, &[(1, &tsumo(500, 1000))], NO_RIICHI)
+        .is_err());
+    assert!(game.apply_multiple_ron(2, &[], NO_RIICHI).is_err());
+}
+
+#[test]
+fn test_game_extension() {
+    let config = GameConfig {
+        length: GameLength::Tonpuusen,
+        ..Default::default()
+    };
+
+    // 誰も返し点に達していなければ南入
+    let mut game = Game::new(&config);
+    for dealer in 0..4 {
+        game.apply_agari((dealer + 1) % 4, Some(dealer), &ron(1000), NO_RIICHI)
+            .unwrap();
+    }
+    assert!(!game.is_finished());
+    assert_eq!((game.round(), game.dealer()), (Wind::Nan, 0));
+
+    // 延長戦は返し点に達した時点で終了
+    game.apply_agari(1, Some(0), &ron(8000), NO_RIICHI).unwrap();
+    assert!(game.is_finished());
+    assert!(game.start_kyoku(0).is_err());
+    assert!(game.apply_ryukyoku([false; 4], NO_RIICHI).is_err());
+    assert_eq!(game.ranking(), [1, 2, 3, 0]);
+
+    // 延長なしなら規定の局で終了
+    let mut game = Game::new(&GameConfig {
+        extension: false,
+        ..config
+    });
+    for dealer in 0..4 {
+        game.apply_agari((dealer + 1) % 4, Some(dealer), &ron(1000), NO_RIICHI)
+            .unwrap();
+    }
+    assert!(game.is_finished());
+}
+
+#[test]
+fn test_game_tobi() {
+    let mut game = Game::new(&GameConfig::default());
+    game.apply_ryukyoku([false; 4], [false, false, true, false])
+        .unwrap();
+    game.apply_agari(1, Some(0), &ron(32000), NO_RIICHI)
+        .unwrap();
+    assert!(game.is_finished());
+    // 供託は和了者が受け取る
+    assert_eq!(game.scores(), [-7000, 58000, 24000, 25000]);
+}
+
+#[test]
+fn test_game_agariyame() {
+    let play = |agariyame: bool| {
+        let mut game = Game::new(&GameConfig {
+            length: GameLength::Tonpuusen,
+            agariyame,
+            ..Default::default()
+        });
+        game.apply_agari(3, Some(0), &ron(8000), NO_RIICHI).unwrap();
+        game.apply_agari(3, Some(1), &ron(1000), NO_RIICHI).unwrap();
+        game.apply_agari(3, Some(2), &ron(1000), NO_RIICHI).unwrap();
+        assert_eq!(game.dealer(), 3);
+        // オーラスで1位の親が和了
+        game.apply_agari(3, Some(0), &ron(1500), NO_RIICHI).unwrap();
+        game
+    };
+    assert!(play(true).is_finished());
+    let game = play(false);
+    assert!(!game.is_finished());
+    assert_eq!((game.dealer(), game.nhonba()), (3, 1));
+}
+
+#[test]
+fn test_game_simulation() {
+    // 和了できれば和了し、それ以外は自摸切りして終了まで進める
+    let mut game = Game::new(&GameConfig::default());
+    let mut seed = 0;
+    while !game.is_finished() {
+        let mut kyoku = game.start_kyoku(seed).unwrap();
+        assert_eq!(kyoku.config().scores, game.scores());
+        while kyoku.phase() != Phase::Finished {
+            for seat in 0..4 {
+                let actions = kyoku.legal_actions(seat);
+                if actions.is_empty() {
+                    continue;
+                }
+                let action = if actions.contains(&Action::Tsumo) {
+                    Action::Tsumo
+                } else if actions.contains(&Action::Ron) {
+                    Action::Ron
+                } else if let Some(drawn) = kyoku.drawn_tile() {
+                    Action::Discard {
+                        tile: drawn,
+                        riichi: false,
+                    }
+                } else {
+                    Action::Pass
+                };
+                kyoku.act(seat, action).unwrap();
+                break;
+            }
+        }
+        game.apply_kyoku_result(&kyoku).unwrap();
+        assert_eq!(
+            game.scores().iter().sum::<i32>() + 1000 * game.nriichi(),
+            100000
+        );
+        seed += 1;
+        assert!(seed < 100);
+    }
+}
@@ -0,0 +1,573 @@
+use mjtools::kyoku::*;
+use mjtools::score::*;
+use mjtools::settlement::*;
+use mjtools::shanten::*;
+use mjtools::types::*;
+use mjtools::wall::*;
+
+// 配牌と親からの自摸順を指定して牌山を積む
+// 13枚に満たない配牌は残りの牌から種類が重ならないように補い、残りは牌の種類順に積む
+fn build_wall(hands: [&str; 4], draws: &str) -> Wall {
+    let mut rest = WallConfig {
+        aka_man: 0,
+        aka_pin: 0,
+        aka_sou: 0,
+    }
+    .all_tiles()
+    .unwrap();
+    let take = |rest: &mut Vec<Tile>, s: &str| -> Vec<Tile> {
+        Tile::from_compactstr(s)
+            .unwrap()
+            .into_iter()
+            .map(|t| {
+                let index = rest.iter().position(|r| *r == t).unwrap();
+                rest.remove(index)
+            })
+            .collect()
+    };
+    let mut hands: Vec<Vec<Tile>> = hands.iter().map(|h| take(&mut rest, h)).collect();
+    let draws = take(&mut rest, draws);
+    for hand in hands.iter_mut() {
+        while hand.len() < 13 {
+            let index = rest
+                .iter()
+                .position(|r| hand.iter().all(|t| t.id != r.id))
+                .unwrap();
+            hand.push(rest.remove(index));
+        }
+    }
+
+    let mut tiles = vec![];
+    for r in 0..3 {
+        for hand in &hands {
+            tiles.extend_from_slice(&hand[4 * r..4 * r + 4]);
+        }
+    }
+    for hand in &hands {
+        tiles.push(hand[12]);
+    }
+    tiles.extend(draws);
+    tiles.extend(rest);
+    Wall::from_tiles(tiles).unwrap()
+}
+
+fn tile(s: &str) -> Tile {
+    Tile::from_compactstr(s).unwrap()[0]
+}
+
+// 宣言待ちのプレイヤーを全員見送らせる
+fn pass_all(kyoku: &mut Kyoku) {
+    for seat in 0..4 {
+        if kyoku.legal_actions(seat).contains(&Action::Pass) {
+            kyoku.act(seat, Action::Pass).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_kyoku_replay() {
+    let config = KyokuConfig::default();
+    for seed in 0..8 {
+        // 和了できれば和了し、それ以外は自摸切りする
+        let mut kyoku = Kyoku::new(&config, seed).unwrap();
+        while kyoku.phase() != Phase::Finished {
+            for seat in 0..4 {
+                let actions = kyoku.legal_actions(seat);
+                if actions.is_empty() {
+                    continue;
+                }
+                let action = if actions.contains(&Action::Tsumo) {
+                    Action::Tsumo
+                } else if actions.contains(&Action::Ron) {
+                    Action::Ron
+                } else if let Some(drawn) = kyoku.drawn_tile() {
+                    Action::Discard {
+                        tile: drawn,
+                        riichi: false,
+                    }
+                } else {
+                    Action::Pass
+                };
+                kyoku.act(seat, action).unwrap();
+                break;
+            }
+        }
+        assert!(kyoku.result().is_some());
+        if let Some(KyokuResult::Ryukyoku { .. }) = kyoku.result() {
+            assert_eq!(kyoku.wall().num_remaining(), 0);
+        }
+
+        // 同じシードと行動の記録から再現できる
+        let replayed = Kyoku::replay(&config, seed, kyoku.log()).unwrap();
+        assert_eq!(replayed, kyoku);
+    }
+}
+
+#[test]
+fn test_kyoku_ryukyoku_karaten() {
+    // 1mを4枚使った単騎待ちは形式聴牌だが和了できない
+    let hands = ["", "1111m234p567p789s", "234m456p789s1122z", ""];
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "")).unwrap();
+    let karaten = kyoku.players()[1].hand.clone();
+    assert_eq!(calculate_hand_shanten(&karaten).unwrap(), 0);
+    while kyoku.phase() != Phase::Finished {
+        if let Phase::Turn { seat } = kyoku.phase() {
+            let drawn = kyoku.drawn_tile().unwrap();
+            kyoku
+                .act(
+                    seat,
+                    Action::Discard {
+                        tile: drawn,
+                        riichi: false,
+                    },
+                )
+                .unwrap();
+        }
+        pass_all(&mut kyoku);
+    }
+    match kyoku.result() {
+        Some(KyokuResult::Ryukyoku { tenpai }) => {
+            assert!(!tenpai[1]);
+            assert!(tenpai[2]);
+        }
+        _ => panic!("expected ryukyoku"),
+    }
+}
+
+#[test]
+fn test_kyoku_tenho() {
+    let wall = build_wall(["123m456p789s1122z", "", "", ""], "1z");
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), wall).unwrap();
+    assert_eq!(kyoku.phase(), Phase::Turn { seat: 0 });
+    assert_eq!(kyoku.drawn_tile(), Some(tile("1z")));
+    assert!(kyoku.act(1, Action::Tsumo).is_err());
+    kyoku.act(0, Action::Tsumo).unwrap();
+
+    assert_eq!(kyoku.phase(), Phase::Finished);
+    match kyoku.result() {
+        Some(KyokuResult::Agari(results)) if results.len() == 1 => {
+            let agari = &results[0];
+            assert_eq!(agari.winner, 0);
+            assert_eq!(agari.loser, None);
+            assert!(agari.info.tsumo && agari.info.tenho);
+            assert_eq!(agari.info.player, Wind::Ton);
+            assert!(agari.score.yaku.iter().any(|(y, _)| *y == Yaku::Tenho));
+        }
+        _ => panic!("expected agari"),
+    }
+}
+
+#[test]
+fn test_kyoku_call_priority() {
+    let hands = ["2m", "34m456p678s234s55p", "22m", ""];
+
+    // ロンはポンより優先
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "7z")).unwrap();
+    kyoku
+        .act(
+            0,
+            Action::Discard {
+                tile: tile("2m"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    assert!(kyoku.legal_actions(1).contains(&Action::Ron));
+    assert!(kyoku.legal_actions(2).contains(&Action::Pon {
+        tiles: [tile("2m"), tile("2m")]
+    }));
+    assert!(kyoku.legal_actions(3).is_empty());
+    kyoku
+        .act(
+            2,
+            Action::Pon {
+                tiles: [tile("2m"), tile("2m")],
+            },
+        )
+        .unwrap();
+    assert!(matches!(kyoku.phase(), Phase::Discarded { .. }));
+    kyoku.act(1, Action::Ron).unwrap();
+    match kyoku.result() {
+        Some(KyokuResult::Agari(results)) if results.len() == 1 => {
+            let agari = &results[0];
+            assert_eq!((agari.winner, agari.loser), (1, Some(0)));
+            assert_eq!(agari.info.player, Wind::Nan);
+            assert!(!agari.info.tsumo);
+        }
+        _ => panic!("expected agari"),
+    }
+
+    // ポンはチーより優先
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "7z")).unwrap();
+    kyoku
+        .act(
+            0,
+            Action::Discard {
+                tile: tile("2m"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    kyoku
+        .act(
+            1,
+            Action::Chi {
+                tiles: [tile("3m"), tile("4m")],
+            },
+        )
+        .unwrap();
+    kyoku
+        .act(
+            2,
+            Action::Pon {
+                tiles: [tile("2m"), tile("2m")],
+            },
+        )
+        .unwrap();
+    assert_eq!(kyoku.phase(), Phase::Turn { seat: 2 });
+    assert_eq!(kyoku.drawn_tile(), None);
+    let players = kyoku.players();
+    assert!(players[0].discards[0].called);
+    assert_eq!(
+        players[2].hand.melds,
+        [Meld::Pung {
+            tiles: [tile("2m"); 3],
+            from: Some(CalledFrom::Toimen),
+        }]
+    );
+    assert_eq!(players[2].hand.hand.len(), 11);
+    // 見逃したロンによる同巡内フリテン
+    assert!(players[1].temporary_furiten);
+    assert!(kyoku.is_furiten(1));
+}
+
+#[test]
+fn test_kyoku_furiten_and_kuikae() {
+    // 見逃した後、自分の打牌で同巡内フリテンが解除される
+    let hands = ["2m", "34m456p678s234s55p", "", ""];
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "7z6z")).unwrap();
+    kyoku
+        .act(
+            0,
+            Action::Discard {
+                tile: tile("2m"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    pass_all(&mut kyoku);
+    assert_eq!(kyoku.phase(), Phase::Turn { seat: 1 });
+    assert!(kyoku.is_furiten(1));
+    kyoku
+        .act(
+            1,
+            Action::Discard {
+                tile: tile("6z"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    assert!(!kyoku.players()[1].temporary_furiten);
+    assert!(!kyoku.is_furiten(1));
+
+    // チーした牌と筋の牌は打牌できない
+    let hands = ["2m", "345m", "", ""];
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "7z")).unwrap();
+    kyoku
+        .act(
+            0,
+            Action::Discard {
+                tile: tile("2m"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    kyoku
+        .act(
+            1,
+            Action::Chi {
+                tiles: [tile("3m"), tile("4m")],
+            },
+        )
+        .unwrap();
+    pass_all(&mut kyoku);
+    assert_eq!(kyoku.phase(), Phase::Turn { seat: 1 });
+    let discards: Vec<TileId> = kyoku
+        .legal_actions(1)
+        .iter()
+        .filter_map(|a| match a {
+            Action::Discard { tile, .. } => Some(tile.id),
+            _ => None,
+        })
+        .collect();
+    assert!(!discards.is_empty());
+    assert!(!discards.contains(&TileId::Id2man));
+    assert!(!discards.contains(&TileId::Id5man));
+
+    // 鳴いた後に喰い替えの牌しか残らなければ鳴けない
+    let hands = ["1m5p7p", "1m1m2m3m4m4m4m5p5p7p7p9p9s", "", ""];
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), build_wall(hands, "")).unwrap();
+    let discard = |kyoku: &mut Kyoku, seat: usize, tile: Tile| {
+        kyoku
+            .act(
+                seat,
+                Action::Discard {
+                    tile,
+                    riichi: false,
+                },
+            )
+            .unwrap();
+    };
+    for (pon, unneeded) in [("5p", "9s"), ("7p", "9p")] {
+        discard(&mut kyoku, 0, tile(pon));
+        kyoku
+            .act(
+                1,
+                Action::Pon {
+                    tiles: [tile(pon), tile(pon)],
+                },
+            )
+            .unwrap();
+        discard(&mut kyoku, 1, tile(unneeded));
+        pass_all(&mut kyoku);
+        for seat in [2, 3] {
+            let drawn = kyoku.drawn_tile().unwrap();
+            discard(&mut kyoku, seat, drawn);
+            pass_all(&mut kyoku);
+        }
+    }
+    assert_eq!(kyoku.players()[1].hand.hand.len(), 7);
+    discard(&mut kyoku, 0, tile("1m"));
+    // 23mでチーすると1m1m4m4m4mが残り、全て喰い替えになる
+    let actions = kyoku.legal_actions(1);
+    assert!(!actions.iter().any(|a| matches!(a, Action::Chi { .. })));
+    assert!(actions.contains(&Action::Pon {
+        tiles: [tile("1m"), tile("1m")]
+    }));
+}
+
+#[test]
+fn test_kyoku_riichi_ippatsu() {
+    let wall = build_wall(["123m456p789s1122z", "", "", ""], "9m8p8s7z1z");
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), wall).unwrap();
+    let riichi = Action::Discard {
+        tile: tile("9m"),
+        riichi: true,
+    };
+    assert!(kyoku.legal_actions(0).contains(&riichi));
+    kyoku.act(0, riichi).unwrap();
+    pass_all(&mut kyoku);
+
+    let dealer = &kyoku.players()[0];
+    assert!(dealer.riichi && dealer.doubleriichi && dealer.ippatsu);
+    assert!(dealer.discards[0].riichi && dealer.discards[0].tsumogiri);
+    assert_eq!(kyoku.nriichi(), 1);
+
+    // 他家は自摸切り
+    for seat in 1..4 {
+        let drawn = kyoku.drawn_tile().unwrap();
+        kyoku
+            .act(
+                seat,
+                Action::Discard {
+                    tile: drawn,
+                    riichi: false,
+                },
+            )
+            .unwrap();
+        pass_all(&mut kyoku);
+    }
+
+    // 立直後は自摸切りか和了のみ
+    assert_eq!(kyoku.drawn_tile(), Some(tile("1z")));
+    assert_eq!(
+        kyoku.legal_actions(0),
+        [
+            Action::Tsumo,
+            Action::Discard {
+                tile: tile("1z"),
+                riichi: false,
+            }
+        ]
+    );
+    kyoku.act(0, Action::Tsumo).unwrap();
+    match kyoku.result() {
+        Some(KyokuResult::Agari(results)) if results.len() == 1 => {
+            let agari = &results[0];
+            assert!(agari.info.doubleriichi && !agari.info.riichi);
+            assert!(agari.info.ippatsu);
+            assert!(!agari.info.tenho);
+            assert_eq!(agari.info.nriichi, 1);
+            assert_eq!(agari.info.dora.ura.len(), 1);
+        }
+        _ => panic!("expected agari"),
+    }
+}
+
+#[test]
+fn test_kyoku_abortive_draw() {
+    // 九種九牌
+    let wall = build_wall(["19m19p19s1234567z", "", "", ""], "5m");
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), wall.clone()).unwrap();
+    assert!(kyoku.legal_actions(0).contains(&Action::KyuushuKyuuhai));
+    kyoku.act(0, Action::KyuushuKyuuhai).unwrap();
+    assert_eq!(kyoku.phase(), Phase::Finished);
+    assert_eq!(
+        kyoku.result(),
+        Some(&KyokuResult::AbortiveDraw(AbortiveDraw::KyuushuKyuuhai {
+            seat: 0
+        }))
+    );
+
+    // 八種では宣言できない、ルールで無効なら宣言できない
+    let kyoku = Kyoku::from_wall(
+        &KyokuConfig::default(),
+        build_wall(["19m19p19s12z", "", "", ""], "5m"),
+    )
+    .unwrap();
+    assert!(!kyoku.legal_actions(0).contains(&Action::KyuushuKyuuhai));
+    let config = KyokuConfig {
+        abortive_draw: AbortiveDrawConfig {
+            kyuushu_kyuuhai: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let kyoku = Kyoku::from_wall(&config, wall).unwrap();
+    assert!(!kyoku.legal_actions(0).contains(&Action::KyuushuKyuuhai));
+
+    // 四風連打
+    let wall = build_wall(["1z", "1z", "1z", "1z"], "");
+    let mut kyoku = Kyoku::from_wall(&KyokuConfig::default(), wall).unwrap();
+    for seat in 0..4 {
+        kyoku
+            .act(
+                seat,
+                Action::Discard {
+                    tile: tile("1z"),
+                    riichi: false,
+                },
+            )
+            .unwrap();
+        pass_all(&mut kyoku);
+    }
+    assert_eq!(
+        kyoku.result(),
+        Some(&KyokuResult::AbortiveDraw(AbortiveDraw::SuufonRenda))
+    );
+
+    // 三家和（無効なら頭ハネ）
+    let hands = [
+        "5p",
+        "234m234p234s678s5p",
+        "345m678p345s678m5p",
+        "456m678p456s678s5p",
+    ];
+    for sanchahou in [true, false] {
+        let config = KyokuConfig {
+            abortive_draw: AbortiveDrawConfig {
+                sanchahou,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut kyoku = Kyoku::from_wall(&config, build_wall(hands, "")).unwrap();
+        kyoku
+            .act(
+                0,
+                Action::Discard {
+                    tile: tile("5p"),
+                    riichi: false,
+                },
+            )
+            .unwrap();
+        for seat in 1..4 {
+            kyoku.act(seat, Action::Ron).unwrap();
+        }
+        match kyoku.result().unwrap() {
+            KyokuResult::AbortiveDraw(AbortiveDraw::Sanchahou) => assert!(sanchahou),
+            KyokuResult::Agari(results) => {
+                assert!(!sanchahou);
+                assert_eq!(results.len(), 1);
+                assert_eq!((results[0].winner, results[0].loser), (1, Some(0)));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
+
+#[test]
+fn test_kyoku_multiple_ron() {
+    let hands = [
+        "5p",
+        "234m234p234s678s5p",
+        "345m678p345s678m5p",
+        "456m678p456s678s5p",
+    ];
+    let config = KyokuConfig {
+        nhonba: 2,
+        nriichi: 1,
+        multiple_ron: MultipleRonRule::MultipleRon,
+        abortive_draw: AbortiveDrawConfig {
+            sanchahou: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut kyoku = Kyoku::from_wall(&config, build_wall(hands, "")).unwrap();
+    kyoku
+        .act(
+            0,
+            Action::Discard {
+                tile: tile("5p"),
+                riichi: false,
+            },
+        )
+        .unwrap();
+    // 2人のロン（対面は見送り）
+    kyoku.act(3, Action::Ron).unwrap();
+    kyoku.act(2, Action::Pass).unwrap();
+    kyoku.act(1, Action::Ron).unwrap();
+    match kyoku.result() {
+        Some(KyokuResult::Agari(results)) => {
+            // 下家から順に並び、全員が局の本場と供託で得点計算する
+            let winners: Vec<usize> = results.iter().map(|a| a.winner).collect();
+            assert_eq!(winners, vec![1, 3]);
+            assert!(results.iter().all(|a| a.loser == Some(0)));
+            assert!(results
+                .iter()
+                .all(|a| (a.info.nhonba, a.info.nriichi) == (2, 1)));
+
+            // 本場と供託は下家のみ
+            let points: Vec<i32> = results
+                .iter()
+                .map(|a| match a.score.point.feed {
+                    Feed::Duck { point } => point,
+                    _ => panic!("expected ron"),
+                })
+                .collect();
+            let wins: Vec<(usize, &Score)> = results.iter().map(|a| (a.winner, &a.score)).collect();
+            let settlement = settle_ron(
+                0,
+                &wins,
+                2,
+                1,
+                &config.rule,
+                MultipleRonRule::MultipleRon,
+                false,
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(
+                settlement.deltas,
+                [
+                    -(points[0] + points[1] - 600),
+                    points[0] + 1000,
+                    0,
+                    points[1] - 600
+                ]
+            );
+        }
+        _ => panic!("expected agari"),
+    }
+}
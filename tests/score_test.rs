@@ -27,20 +27,508 @@ struct ScoreTestCase {
     tsumo: bool,
     riichi: bool,
     ippatsu: bool,
-    answer: Score,
+    answer: ScoreAnswer,
+}
+
+// 得点の期待値
+#[derive(Debug, PartialEq, Eq)]
+struct ScoreAnswer {
+    han: i32,
+    fu: i32,
+    yaku: HashSet<Yaku>,
+    point: Point,
+}
+
+impl From<Score> for ScoreAnswer {
+    fn from(score: Score) -> Self {
+        // 符の内訳は合計が一致しているか確認
+        assert_eq!(
+            score.fu_detail.iter().map(|(_, f)| f).sum::<i32>(),
+            score.fu,
+            "Inconsistent fu detail: {:?}",
+            score.fu_detail
+        );
+        ScoreAnswer {
+            han: score.han,
+            fu: score.fu,
+            yaku: score.yaku.iter().map(|(y, _)| *y).collect(),
+            point: score.point,
+        }
+    }
 }
 
 // 全テストケース実行
 #[test]
 fn test_score() {
     for test in NORMAL_TESTS.iter() {
-        do_testcase(&test);
+        do_testcase(test);
     }
     for test in YAKUMAN_TESTS.iter() {
-        do_testcase(&test);
+        do_testcase(test);
+    }
+}
+
+// 異なるルールでの得点計算を並列に実行
+#[test]
+fn test_score_with_rules() {
+    let default_rule = ScoreRuleConfig::default();
+    let house_rule = ScoreRuleConfig {
+        kokushi13_as_double: false,
+        suankotanki_as_double: false,
+        mangan_roundup: true,
+        ..ScoreRuleConfig::default()
+    };
+
+    std::thread::scope(|s| {
+        for (rule, tests) in [
+            (default_rule, &*DEFAULT_RULE_TESTS),
+            (house_rule, &*HOUSE_RULE_TESTS),
+        ] {
+            s.spawn(move || {
+                for _ in 0..100 {
+                    for (test, answer) in tests.iter() {
+                        let info = construct_agari_information(test);
+                        let get = calculate_score_with_rules(&info, &rule)
+                            .ok()
+                            .map(ScoreAnswer::from);
+                        assert_eq!(
+                            get, *answer,
+                            "Assertion failed. Get:{:?} Answer:{:?}",
+                            get, answer
+                        );
+                    }
+                }
+            });
+        }
+    });
+}
+
+// 符の内訳
+#[test]
+fn test_fu_detail() {
+    for (test, answer) in FU_DETAIL_TESTS.iter() {
+        do_testcase(test);
+        let get = calculate_score(&construct_agari_information(test))
+            .unwrap()
+            .fu_detail;
+        // 面子の並びは不定なので要素の一致を確認
+        assert!(
+            get.len() == answer.len() && answer.iter().all(|a| get.contains(a)),
+            "Assertion failed. Get:{:?} Answer:{:?}",
+            get,
+            answer
+        );
+    }
+}
+
+// 役ごとの翻数
+#[test]
+fn test_yaku_han_detail() {
+    let parse = |s: &str| Tile::from_mjscorestr(s).unwrap();
+    let pung = parse("白白白");
+    let info = AgariInformation {
+        wining_tile: parse("7m")[0],
+        hand: Hand {
+            hand: parse("3m4m5M6m6m6m8m9m発発"),
+            melds: vec![Meld::Pung {
+                tiles: [pung[0], pung[1], pung[2]],
+                from: None,
+            }],
+        },
+        nhonba: 0,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Pee,
+        tsumo: false,
+        riichi: false,
+        ippatsu: false,
+        doubleriichi: false,
+        haitei: false,
+        rinshan: false,
+        chankan: false,
+        nagashimangan: false,
+        tenho: false,
+        chiho: false,
+        dora: Dora {
+            omote: parse("4m"),
+            ura: parse("2m"),
+        },
+    };
+    // 鳴いた混一色は2翻, ドラは表/赤/裏で別に数える
+    assert_eq!(
+        calculate_score(&info).unwrap().yaku,
+        vec![
+            (Yaku::Haku, YakuValue::Han(1)),
+            (Yaku::Honitsu, YakuValue::Han(2)),
+            (Yaku::Dora, YakuValue::Han(1)),
+            (Yaku::Akadora, YakuValue::Han(1)),
+            (Yaku::Uradora, YakuValue::Han(1)),
+        ]
+    );
+
+    // 役満は倍数で表す
+    let info = construct_agari_information(&rule_testcase_kokushimusou13());
+    assert_eq!(
+        calculate_score_with_rules(&info, &ScoreRuleConfig::default())
+            .unwrap()
+            .yaku,
+        vec![(Yaku::Kokushimusou13, YakuValue::Yakuman(2))]
+    );
+    assert_eq!(
+        calculate_score_with_rules(
+            &info,
+            &ScoreRuleConfig {
+                kokushi13_as_double: false,
+                ..ScoreRuleConfig::default()
+            }
+        )
+        .unwrap()
+        .yaku,
+        vec![(Yaku::Kokushimusou13, YakuValue::Yakuman(1))]
+    );
+
+    // 全ケースで役の翻数の合計が一致するか確認
+    for test in NORMAL_TESTS.iter().chain(YAKUMAN_TESTS.iter()) {
+        let score = calculate_score(&construct_agari_information(test)).unwrap();
+        let han: i32 = score
+            .yaku
+            .iter()
+            .map(|(_, v)| match v {
+                YakuValue::Han(n) => *n,
+                YakuValue::Yakuman(n) => 13 * n,
+            })
+            .sum();
+        assert_eq!(han, score.han, "{:?}", score.yaku);
     }
 }
 
+// 和了形の解釈の列挙
+#[test]
+fn test_agari_interpretations() {
+    let construct_info = |hand: &str, wining_tile: &str, tsumo: bool| AgariInformation {
+        wining_tile: Tile::from_mjscorestr(wining_tile).unwrap()[0],
+        hand: Hand {
+            hand: Tile::from_mjscorestr(hand).unwrap(),
+            melds: vec![],
+        },
+        nhonba: 0,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Nan,
+        tsumo,
+        riichi: false,
+        ippatsu: false,
+        doubleriichi: false,
+        haitei: false,
+        rinshan: false,
+        chankan: false,
+        nagashimangan: false,
+        tenho: false,
+        chiho: false,
+        dora: Dora {
+            omote: vec![],
+            ura: vec![],
+        },
+    };
+    let rule = ScoreRuleConfig::default();
+
+    // 三暗刻と一盃口の2通りの解釈
+    let info = construct_info("1m1m1m2m2m2m3m3m4p5p6p9s9s", "3m", true);
+    let interpretations = listup_agari_interpretations(&info, &rule).unwrap();
+    assert_eq!(interpretations.len(), 2);
+    let selected: Vec<&AgariInterpretation> =
+        interpretations.iter().filter(|i| i.selected).collect();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(
+        selected[0].hand,
+        DividedHand {
+            atama: TileId::Id9sou,
+            mentsu: vec![
+                Mentsu::Anko { t: TileId::Id1man },
+                Mentsu::Anko { t: TileId::Id2man },
+                Mentsu::Anko { t: TileId::Id3man },
+                Mentsu::Syuntsu {
+                    min: TileId::Id4pin
+                },
+            ],
+            wait: Wait::Shanpon,
+        }
+    );
+    assert_eq!(
+        selected[0].yaku,
+        vec![
+            (Yaku::Tsumo, YakuValue::Han(1)),
+            (Yaku::Sananko, YakuValue::Han(2)),
+        ]
+    );
+    assert_eq!((selected[0].han, selected[0].fu), (3, 40));
+    let other = interpretations.iter().find(|i| !i.selected).unwrap();
+    assert_eq!(other.hand.wait, Wait::Penchan);
+    assert_eq!((other.han, other.fu), (2, 30));
+    let score = calculate_score(&info).unwrap();
+    assert_eq!((score.han, score.fu), (selected[0].han, selected[0].fu));
+
+    // 両面/嵌張のどちらにも取れる場合は別の解釈として列挙
+    let info = construct_info("4m5m6m6m7m1p2p3p7s8s9s5p5p", "5m", false);
+    let interpretations = listup_agari_interpretations(&info, &rule).unwrap();
+    assert_eq!(interpretations.len(), 2);
+    let selected = interpretations.iter().find(|i| i.selected).unwrap();
+    assert_eq!(selected.hand.wait, Wait::Ryanmen);
+    assert_eq!(selected.yaku, vec![(Yaku::Pinfu, YakuValue::Han(1))]);
+    let other = interpretations.iter().find(|i| !i.selected).unwrap();
+    assert_eq!(other.hand.wait, Wait::Kanchan);
+    assert!(other.yaku.is_empty());
+
+    // 二盃口の形は七対子（断么九 七対子 3翻25符 3200点）より高い二盃口で計算
+    let info = construct_info("2m2m3m3m4m4m5p5p6p6p7p7p8s", "8s", false);
+    let score = calculate_score(&info).unwrap();
+    assert_eq!(
+        score.yaku,
+        vec![
+            (Yaku::Tanyao, YakuValue::Han(1)),
+            (Yaku::Ryanpeko, YakuValue::Han(3)),
+        ]
+    );
+    assert_eq!((score.han, score.fu, score.point.get), (4, 40, 8000));
+    let interpretations = listup_agari_interpretations(&info, &rule).unwrap();
+    assert_eq!(interpretations.len(), 1);
+    assert!(interpretations[0].selected);
+
+    // 二盃口の形でない七対子はそのまま
+    let info = construct_info("2m2m3m3m4m4m5p5p6p6p8p8p8s", "8s", false);
+    let score = calculate_score(&info).unwrap();
+    assert_eq!(
+        score.yaku,
+        vec![
+            (Yaku::Tanyao, YakuValue::Han(1)),
+            (Yaku::Chitoitsu, YakuValue::Han(2)),
+        ]
+    );
+    assert_eq!((score.han, score.fu), (3, 25));
+}
+
+// 待ち牌と待ちの形の列挙
+#[test]
+fn test_waiting_tiles() {
+    let construct_hand = |hand: &str, melds: Vec<Meld>| Hand {
+        hand: Tile::from_mjscorestr(hand).unwrap(),
+        melds,
+    };
+    let waits_of = |hand: &Hand| -> Vec<(TileId, Vec<Wait>, Vec<WaitPattern>)> {
+        listup_waiting_tiles(hand)
+            .unwrap()
+            .into_iter()
+            .map(|wt| (wt.tile, wt.waits, wt.patterns))
+            .collect()
+    };
+    use TileId::*;
+    use Wait::*;
+    use WaitPattern::*;
+
+    // 三面張
+    assert_eq!(
+        waits_of(&construct_hand("2m3m4m5m6m1p2p3p7s8s9s東東", vec![])),
+        vec![
+            (Id1man, vec![Ryanmen], vec![Sanmenchan]),
+            (Id4man, vec![Ryanmen], vec![Sanmenchan]),
+            (Id7man, vec![Ryanmen], vec![Sanmenchan]),
+        ]
+    );
+    // 延べ単
+    assert_eq!(
+        waits_of(&construct_hand("1m2m3m4m5p6p7p7s8s9s東東東", vec![])),
+        vec![
+            (Id1man, vec![Tanki], vec![Nobetan]),
+            (Id4man, vec![Tanki], vec![Nobetan]),
+        ]
+    );
+    // 煙突
+    assert_eq!(
+        waits_of(&construct_hand("3m3m3m4m5m1p2p3p7s8s9s東東", vec![])),
+        vec![
+            (Id3man, vec![Shanpon, Ryanmen], vec![Entotsu]),
+            (Id6man, vec![Ryanmen], vec![Entotsu]),
+            (IdTon, vec![Shanpon], vec![Entotsu]),
+        ]
+    );
+    // 両面と嵌張の両方に取れる
+    assert_eq!(
+        waits_of(&construct_hand("5m6m7m2p3p4p3s3s5s6s7s7s8s", vec![])),
+        vec![
+            (Id6sou, vec![Kanchan, Ryanmen], vec![]),
+            (Id9sou, vec![Ryanmen], vec![]),
+        ]
+    );
+    // 七対子の単騎待ち
+    assert_eq!(
+        waits_of(&construct_hand("1m1m3m3m5m5m7m7m9m9m東東白", vec![])),
+        vec![(IdHaku, vec![Tanki], vec![])]
+    );
+    // 国士無双13面待ち
+    let waits = waits_of(&construct_hand("1m9m1p9p1s9s東南西北白発中", vec![]));
+    assert_eq!(waits.len(), 13);
+    assert!(waits.iter().all(|(_, w, _)| *w == vec![Tanki]));
+    // 副露を含む手
+    let pung = Tile {
+        id: IdHaku,
+        aka: false,
+    };
+    assert_eq!(
+        waits_of(&construct_hand(
+            "1m2m4p5p6p7s8s9s東東",
+            vec![Meld::Pung {
+                tiles: [pung, pung, pung],
+                from: None,
+            }]
+        )),
+        vec![(Id3man, vec![Penchan], vec![])]
+    );
+    // 自身で4枚使っている牌は待ちにならない
+    assert_eq!(
+        waits_of(&construct_hand("1m1m1m1m2m3m4m5m6m7m8m9m9m", vec![]))
+            .iter()
+            .map(|(t, _, _)| *t)
+            .collect::<Vec<TileId>>(),
+        vec![Id3man, Id6man, Id9man]
+    );
+    // 聴牌していない
+    assert!(waits_of(&construct_hand("1m2m4m5p6p7p7s8s9s東南西北", vec![])).is_empty());
+    // 牌数が不正
+    assert!(listup_waiting_tiles(&construct_hand("1m2m3m", vec![])).is_err());
+}
+
+// 場風の雀頭は平和にならない
+#[test]
+fn test_pinfu_with_round_wind_atama() {
+    let info = AgariInformation {
+        wining_tile: Tile::from_mjscorestr("7m").unwrap()[0],
+        hand: Hand {
+            hand: Tile::from_mjscorestr("2m3m4m5m6m1p2p3p7s8s9s東東").unwrap(),
+            melds: vec![],
+        },
+        nhonba: 0,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Nan,
+        tsumo: false,
+        riichi: true,
+        ippatsu: false,
+        doubleriichi: false,
+        haitei: false,
+        rinshan: false,
+        chankan: false,
+        nagashimangan: false,
+        tenho: false,
+        chiho: false,
+        dora: Dora {
+            omote: vec![],
+            ura: vec![],
+        },
+    };
+    let score = calculate_score(&info).unwrap();
+    assert_eq!(score.yaku, vec![(Yaku::Riichi, YakuValue::Han(1))]);
+    assert_eq!(score.fu, 40);
+
+    // 客風の雀頭なら平和
+    let info = AgariInformation {
+        hand: Hand {
+            hand: Tile::from_mjscorestr("2m3m4m5m6m1p2p3p7s8s9s西西").unwrap(),
+            melds: vec![],
+        },
+        ..info
+    };
+    let score = calculate_score(&info).unwrap();
+    assert_eq!(
+        score.yaku,
+        vec![
+            (Yaku::Riichi, YakuValue::Han(1)),
+            (Yaku::Pinfu, YakuValue::Han(1)),
+        ]
+    );
+    assert_eq!(score.fu, 30);
+}
+
+// ルールファイル/プリセットの読み込み
+#[test]
+fn test_rule_config_from_str() {
+    assert_eq!(
+        ScoreRuleConfig::from_rulestr("").unwrap(),
+        ScoreRuleConfig::default()
+    );
+    assert_eq!(
+        ScoreRuleConfig::from_preset("Tenhou").unwrap(),
+        ScoreRuleConfig {
+            kokushi13_as_double: false,
+            suankotanki_as_double: false,
+            ..ScoreRuleConfig::default()
+        }
+    );
+    assert_eq!(
+        ScoreRuleConfig::from_rulestr(
+            "# 天鳳ルールから変更\n\
+             preset = \"tenhou\"\n\
+             \n\
+             mangan_roundup = true # 切り上げ満貫\n\
+             nagashimangan=false\n"
+        )
+        .unwrap(),
+        ScoreRuleConfig {
+            kokushi13_as_double: false,
+            suankotanki_as_double: false,
+            mangan_roundup: true,
+            nagashimangan: false,
+            ..ScoreRuleConfig::default()
+        }
+    );
+    for name in ScoreRuleConfig::PRESETS {
+        assert!(ScoreRuleConfig::from_preset(name).is_ok());
+    }
+
+    // 不正な記述
+    for invalid in [
+        "kuitan = true\nkuitan = false",
+        "kuitan = yes",
+        "kuitan",
+        "unknown_rule = true",
+        "preset = tenhou",
+        "preset = \"unknown\"",
+    ] {
+        assert!(
+            ScoreRuleConfig::from_rulestr(invalid).is_err(),
+            "{} must be invalid",
+            invalid
+        );
+    }
+    // 値より先にキーを確認
+    assert_eq!(
+        ScoreRuleConfig::from_rulestr("akadora = 3")
+            .unwrap_err()
+            .to_string(),
+        "line 1: unknown rule key 'akadora'"
+    );
+    // "で囲まれた中の#はコメントではない
+    assert_eq!(
+        ScoreRuleConfig::from_rulestr("preset = \"tenhou#1\" # コメント")
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "Unknown rule preset 'tenhou#1' (expected one of: {})",
+            ScoreRuleConfig::PRESETS.join(", ")
+        )
+    );
+    assert!(
+        ScoreRuleConfig::from_rulestr("kuitan = true # \"#\"")
+            .unwrap()
+            .kuitan
+    );
+    assert!(
+        ScoreRuleConfig::from_rulestr("kuitan = true\nakadora = true")
+            .unwrap_err()
+            .to_string()
+            .contains("line 2")
+    );
+}
+
 // 副露の組み立て
 fn construct_meld_from_char(min_ch: char, ty: TestMeld) -> Meld {
     let t = Tile {
@@ -48,7 +536,10 @@ fn construct_meld_from_char(min_ch: char, ty: TestMeld) -> Meld {
         aka: false,
     };
     match ty {
-        TestMeld::Pung => Meld::Pung { tiles: [t, t, t] },
+        TestMeld::Pung => Meld::Pung {
+            tiles: [t, t, t],
+            from: None,
+        },
         TestMeld::Chow => {
             assert!(t.id.is_suhai());
             let t1 = Tile {
@@ -59,22 +550,28 @@ fn construct_meld_from_char(min_ch: char, ty: TestMeld) -> Meld {
                 id: t.id.nth(2),
                 aka: false,
             };
-            Meld::Chow { tiles: [t, t1, t2] }
+            Meld::Chow {
+                tiles: [t, t1, t2],
+                from: None,
+            }
         }
         TestMeld::Ankan => Meld::Ankan {
             tiles: [t, t, t, t],
         },
         TestMeld::Minkan => Meld::Minkan {
             tiles: [t, t, t, t],
+            from: None,
         },
         TestMeld::Kakan => Meld::Kakan {
             tiles: [t, t, t, t],
+            from: None,
+            added: t,
         },
     }
 }
 
-// テストケース実行
-fn do_testcase(case: &ScoreTestCase) {
+// 和了情報の構成
+fn construct_agari_information(case: &ScoreTestCase) -> AgariInformation {
     let construct_tiles_from_tilestr = |s: &str| -> Vec<Tile> {
         TileId::from_tilestr(s)
             .unwrap()
@@ -95,15 +592,12 @@ fn do_testcase(case: &ScoreTestCase) {
         omote: construct_tiles_from_tilestr(case.omotedora_str),
         ura: construct_tiles_from_tilestr(case.uradora_str),
     };
-    let info = AgariInformation {
+    AgariInformation {
         wining_tile: Tile {
             id: TileId::from_char(case.wining_tile_ch).unwrap(),
             aka: false,
         },
-        hand: Hand {
-            hand: hand,
-            melds: meld,
-        },
+        hand: Hand { hand, melds: meld },
         nhonba: case.nhonba,
         nriichi: case.nriichi,
         round: case.round,
@@ -118,9 +612,14 @@ fn do_testcase(case: &ScoreTestCase) {
         nagashimangan: false,
         tenho: false,
         chiho: false,
-        dora: dora,
-    };
-    let get = calculate_score(&info).unwrap();
+        dora,
+    }
+}
+
+// テストケース実行
+fn do_testcase(case: &ScoreTestCase) {
+    let info = construct_agari_information(case);
+    let get = ScoreAnswer::from(calculate_score(&info).unwrap());
     assert_eq!(
         get, case.answer,
         "Assertion failed. Get:{:?} Answer:{:?}",
@@ -143,7 +642,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Riichi,]),
@@ -166,7 +665,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 60,
                 yaku: HashSet::from([Yaku::Chun, Yaku::Dora,]),
@@ -193,7 +692,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 70,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Chun, Yaku::Dora,]),
@@ -216,7 +715,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 7,
                 fu: 50,
                 yaku: HashSet::from([Yaku::Sananko, Yaku::Sansyokudoukoku, Yaku::Dora,]),
@@ -239,7 +738,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Dora,]),
@@ -266,7 +765,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Bakaze,]),
@@ -289,7 +788,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 20,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Pinfu,]),
@@ -312,7 +811,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Pinfu, Yaku::Dora,]),
@@ -335,7 +834,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Pinfu, Yaku::Dora,]),
@@ -358,7 +857,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tanyao, Yaku::Pinfu, Yaku::Dora,]),
@@ -381,7 +880,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -404,7 +903,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -427,7 +926,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Honitsu,]),
@@ -453,7 +952,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Chinitsu,]),
@@ -479,7 +978,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Chun,]),
@@ -502,7 +1001,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 8,
                 fu: 30,
                 yaku: HashSet::from([
@@ -535,10 +1034,10 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
-                yaku: HashSet::from([Yaku::Haku, Yaku::Dora,]),
+                yaku: HashSet::from([Yaku::Haku, Yaku::Dora, Yaku::Uradora,]),
                 point: Point {
                     get: 4000,
                     feed: Feed::Tsumo {
@@ -561,7 +1060,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: true,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Ippatsu, Yaku::Dora,]),
@@ -584,7 +1083,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -607,7 +1106,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -630,7 +1129,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao,]),
@@ -653,7 +1152,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: true,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 30,
                 yaku: HashSet::from([
@@ -682,7 +1181,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Jikaze, Yaku::Chun,]),
@@ -705,7 +1204,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 20,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Pinfu, Yaku::Ikkitsukan,]),
@@ -728,7 +1227,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 50,
                 yaku: HashSet::from([Yaku::Bakaze, Yaku::Hatu, Yaku::Chanta, Yaku::Honitsu,]),
@@ -754,7 +1253,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 25,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Tanyao, Yaku::Chitoitsu,]),
@@ -784,7 +1283,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Jikaze, Yaku::Toitoiho,]),
@@ -807,7 +1306,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 25,
                 yaku: HashSet::from([Yaku::Chitoitsu, Yaku::Honrouto,]),
@@ -830,7 +1329,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 50,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Chun, Yaku::Sananko, Yaku::Syosangen,]),
@@ -857,7 +1356,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 70,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Sankantsu,]),
@@ -880,7 +1379,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 20,
                 yaku: HashSet::from([Yaku::Tsumo, Yaku::Tanyao, Yaku::Pinfu, Yaku::Ryanpeko,]),
@@ -906,7 +1405,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 30,
                 yaku: HashSet::from([
@@ -934,7 +1433,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 60,
                 yaku: HashSet::from([
@@ -965,10 +1464,10 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 8,
                 fu: 40,
-                yaku: HashSet::from([Yaku::Riichi, Yaku::Dora,]),
+                yaku: HashSet::from([Yaku::Riichi, Yaku::Dora, Yaku::Uradora,]),
                 point: Point {
                     get: 16000,
                     feed: Feed::Duck { point: 16000 },
@@ -988,7 +1487,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Dora,]),
@@ -1011,7 +1510,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Ikkitsukan,]),
@@ -1037,7 +1536,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 20,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Pinfu, Yaku::Dora,]),
@@ -1063,7 +1562,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 30,
                 yaku: HashSet::from([
@@ -1095,7 +1594,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 5,
                 fu: 30,
                 yaku: HashSet::from([
@@ -1127,7 +1626,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 50,
                 yaku: HashSet::from([Yaku::Hatu, Yaku::Dora,]),
@@ -1150,7 +1649,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -1173,7 +1672,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tsumo, Yaku::Tanyao, Yaku::Dora,]),
@@ -1199,7 +1698,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Honitsu,]),
@@ -1222,7 +1721,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Jikaze, Yaku::Dora,]),
@@ -1245,7 +1744,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Bakaze,]),
@@ -1268,7 +1767,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Sansyokudoujyun, Yaku::Dora,]),
@@ -1291,7 +1790,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
@@ -1317,7 +1816,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Ipeko, Yaku::Dora,]),
@@ -1340,7 +1839,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Jikaze,]),
@@ -1363,7 +1862,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Chun, Yaku::Dora,]),
@@ -1386,7 +1885,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 4,
                 fu: 20,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Pinfu, Yaku::Dora,]),
@@ -1412,7 +1911,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao,]),
@@ -1435,7 +1934,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Toitoiho,]),
@@ -1458,7 +1957,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Sansyokudoujyun,]),
@@ -1481,7 +1980,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 40,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Dora,]),
@@ -1504,7 +2003,7 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: true,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 3,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Ippatsu, Yaku::Pinfu,]),
@@ -1527,7 +2026,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 2,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Sansyokudoujyun, Yaku::Dora,]),
@@ -1550,7 +2049,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Hatu,]),
@@ -1573,7 +2072,7 @@ lazy_static! {
             tsumo: true,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 25,
                 yaku: HashSet::from([Yaku::Riichi, Yaku::Tsumo, Yaku::Chitoitsu, Yaku::Dora,]),
@@ -1599,10 +2098,10 @@ lazy_static! {
             tsumo: false,
             riichi: true,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 30,
-                yaku: HashSet::from([Yaku::Riichi, Yaku::Pinfu, Yaku::Dora,]),
+                yaku: HashSet::from([Yaku::Riichi, Yaku::Pinfu, Yaku::Dora, Yaku::Uradora,]),
                 point: Point {
                     get: 12000,
                     feed: Feed::Duck { point: 12000 },
@@ -1622,7 +2121,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 1,
                 fu: 30,
                 yaku: HashSet::from([Yaku::Tanyao,]),
@@ -1645,10 +2144,10 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 6,
                 fu: 25,
-                yaku: HashSet::from([Yaku::Chitoitsu, Yaku::Dora,]),
+                yaku: HashSet::from([Yaku::Chitoitsu, Yaku::Dora, Yaku::Uradora,]),
                 point: Point {
                     get: 12000,
                     feed: Feed::Duck { point: 12000 },
@@ -1670,7 +2169,7 @@ lazy_static! {
             tsumo: true,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Suanko,]),
@@ -1693,7 +2192,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 26,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Suankotanki,]),
@@ -1716,7 +2215,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Daisangen,]),
@@ -1739,7 +2238,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Kokushimusou,]),
@@ -1762,7 +2261,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 26,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Kokushimusou13,]),
@@ -1785,7 +2284,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Ryuiso,]),
@@ -1808,7 +2307,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Tsuiso,]),
@@ -1831,7 +2330,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Chinroto,]),
@@ -1859,7 +2358,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Sukantsu,]),
@@ -1882,7 +2381,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Daisushi,]),
@@ -1905,7 +2404,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Syosushi,]),
@@ -1928,7 +2427,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 13,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Churenpouton,]),
@@ -1951,7 +2450,7 @@ lazy_static! {
             tsumo: false,
             riichi: false,
             ippatsu: false,
-            answer: Score {
+            answer: ScoreAnswer {
                 han: 26,
                 fu: 0,
                 yaku: HashSet::from([Yaku::Churenpouton9,]),
@@ -1962,4 +2461,290 @@ lazy_static! {
             },
         },
     ];
+
+    // 標準ルールでの得点
+    static ref DEFAULT_RULE_TESTS: Vec<(ScoreTestCase, Option<ScoreAnswer>)> = vec![
+        (
+            rule_testcase_kokushimusou13(),
+            Some(ScoreAnswer {
+                han: 26,
+                fu: 0,
+                yaku: HashSet::from([Yaku::Kokushimusou13,]),
+                point: Point {
+                    get: 64000,
+                    feed: Feed::Duck { point: 64000 },
+                },
+            })
+        ),
+        (
+            rule_testcase_4han30fu(),
+            Some(ScoreAnswer {
+                han: 4,
+                fu: 30,
+                yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
+                point: Point {
+                    get: 7700,
+                    feed: Feed::Duck { point: 7700 },
+                },
+            })
+        ),
+    ];
+
+    // ダブル役満なし/満貫切り上げありルールでの得点
+    static ref HOUSE_RULE_TESTS: Vec<(ScoreTestCase, Option<ScoreAnswer>)> = vec![
+        (
+            rule_testcase_kokushimusou13(),
+            Some(ScoreAnswer {
+                han: 13,
+                fu: 0,
+                yaku: HashSet::from([Yaku::Kokushimusou13,]),
+                point: Point {
+                    get: 32000,
+                    feed: Feed::Duck { point: 32000 },
+                },
+            })
+        ),
+        (
+            rule_testcase_4han30fu(),
+            Some(ScoreAnswer {
+                han: 4,
+                fu: 30,
+                yaku: HashSet::from([Yaku::Tanyao, Yaku::Dora,]),
+                point: Point {
+                    get: 8000,
+                    feed: Feed::Duck { point: 8000 },
+                },
+            })
+        ),
+    ];
+
+    // 符の内訳のテストケース
+    static ref FU_DETAIL_TESTS: Vec<(ScoreTestCase, Vec<(Fu, i32)>)> = vec![
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀓',
+                hand_str: "🀊🀋🀌🀡🀡🀒🀒🀒🀓🀓🀗🀗🀗",
+                meld: vec![],
+                omotedora_str: "",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Nan,
+                player: Wind::Nan,
+                tsumo: false,
+                riichi: true,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 1,
+                    fu: 40,
+                    yaku: HashSet::from([Yaku::Riichi,]),
+                    point: Point {
+                        get: 1300,
+                        feed: Feed::Duck { point: 1300 },
+                    },
+                },
+            },
+            vec![
+                (Fu::Futei, 20),
+                (Fu::Menzen, 10),
+                (Fu::Anko { t: TileId::Id3sou }, 4),
+                (Fu::Minko { t: TileId::Id4sou }, 2),
+                (Fu::Anko { t: TileId::Id8sou }, 4),
+            ]
+        ),
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀗',
+                hand_str: "🀈🀉🀊🀞🀟🀠🀒🀓🀔🀕🀖🀋🀋",
+                meld: vec![],
+                omotedora_str: "",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Ton,
+                player: Wind::Nan,
+                tsumo: true,
+                riichi: false,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 3,
+                    fu: 20,
+                    yaku: HashSet::from([Yaku::Pinfu, Yaku::Tsumo, Yaku::Tanyao,]),
+                    point: Point {
+                        get: 2700,
+                        feed: Feed::Tsumo { ko: 700, oya: 1300 },
+                    },
+                },
+            },
+            vec![(Fu::Futei, 20), (Fu::Pinfu, 0)]
+        ),
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀓',
+                hand_str: "🀚🀛🀜🀒🀔🀠🀠",
+                meld: vec![('🀍', TestMeld::Chow), ('🀄', TestMeld::Ankan),],
+                omotedora_str: "🀟",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Ton,
+                player: Wind::Ton,
+                tsumo: false,
+                riichi: false,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 3,
+                    fu: 60,
+                    yaku: HashSet::from([Yaku::Chun, Yaku::Dora,]),
+                    point: Point {
+                        get: 11600,
+                        feed: Feed::Duck { point: 11600 },
+                    },
+                },
+            },
+            vec![
+                (Fu::Futei, 20),
+                (Fu::Kanchan, 2),
+                (Fu::Ankan { t: TileId::IdChun }, 32),
+                (Fu::Roundup, 6),
+            ]
+        ),
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀆',
+                hand_str: "🀇🀇🀈🀈🀙🀙🀝🀝🀐🀐🀀🀀🀆",
+                meld: vec![],
+                omotedora_str: "",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Ton,
+                player: Wind::Nan,
+                tsumo: false,
+                riichi: true,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 3,
+                    fu: 25,
+                    yaku: HashSet::from([Yaku::Chitoitsu, Yaku::Riichi,]),
+                    point: Point {
+                        get: 3200,
+                        feed: Feed::Duck { point: 3200 },
+                    },
+                },
+            },
+            vec![(Fu::Chitoitsu, 25)]
+        ),
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀗',
+                hand_str: "🀈🀉🀊🀞🀟🀠🀕🀖🀋🀋",
+                meld: vec![('🀒', TestMeld::Chow)],
+                omotedora_str: "",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Ton,
+                player: Wind::Nan,
+                tsumo: false,
+                riichi: false,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 1,
+                    fu: 30,
+                    yaku: HashSet::from([Yaku::Tanyao,]),
+                    point: Point {
+                        get: 1000,
+                        feed: Feed::Duck { point: 1000 },
+                    },
+                },
+            },
+            vec![(Fu::Futei, 20), (Fu::KuiPinfu, 10)]
+        ),
+        // 和了牌が副露したチーの嵌張の位置にあっても、手牌の両面待ちなら待ちの符はつかない
+        (
+            ScoreTestCase {
+                wining_tile_ch: '🀋',
+                hand_str: "🀌🀍🀚🀛🀜🀆🀆🀆🀅🀅",
+                meld: vec![('🀊', TestMeld::Chow)],
+                omotedora_str: "",
+                uradora_str: "",
+                nhonba: 0,
+                nriichi: 0,
+                round: Wind::Ton,
+                player: Wind::Nan,
+                tsumo: false,
+                riichi: false,
+                ippatsu: false,
+                answer: ScoreAnswer {
+                    han: 1,
+                    fu: 30,
+                    yaku: HashSet::from([Yaku::Haku,]),
+                    point: Point {
+                        get: 1000,
+                        feed: Feed::Duck { point: 1000 },
+                    },
+                },
+            },
+            vec![
+                (Fu::Futei, 20),
+                (Fu::YakuhaiAtama { t: TileId::IdHatu }, 2),
+                (Fu::Anko { t: TileId::IdHaku }, 8),
+            ]
+        ),
+    ];
+}
+
+// ルール比較用: 国士無双13面待ち
+fn rule_testcase_kokushimusou13() -> ScoreTestCase {
+    ScoreTestCase {
+        wining_tile_ch: '🀇',
+        hand_str: "🀇🀏🀙🀡🀐🀘🀀🀁🀂🀃🀆🀅🀄",
+        meld: vec![],
+        omotedora_str: "",
+        uradora_str: "",
+        nhonba: 0,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Nan,
+        tsumo: false,
+        riichi: false,
+        ippatsu: false,
+        answer: ScoreAnswer {
+            han: 0,
+            fu: 0,
+            yaku: HashSet::new(),
+            point: Point {
+                get: 0,
+                feed: Feed::Duck { point: 0 },
+            },
+        },
+    }
+}
+
+// ルール比較用: 喰いタンドラ3の4翻30符
+fn rule_testcase_4han30fu() -> ScoreTestCase {
+    ScoreTestCase {
+        wining_tile_ch: '🀚',
+        hand_str: "🀈🀉🀊🀛🀜🀓🀔🀕🀖🀖",
+        meld: vec![('🀝', TestMeld::Pung)],
+        omotedora_str: "🀜",
+        uradora_str: "",
+        nhonba: 0,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Nan,
+        tsumo: false,
+        riichi: false,
+        ippatsu: false,
+        answer: ScoreAnswer {
+            han: 0,
+            fu: 0,
+            yaku: HashSet::new(),
+            point: Point {
+                get: 0,
+                feed: Feed::Duck { point: 0 },
+            },
+        },
+    }
 }
@@ -0,0 +1,145 @@
+#![cfg(feature = "serde")]
+
+use mjtools::game::*;
+use mjtools::kyoku::*;
+use mjtools::score::*;
+use mjtools::types::*;
+use mjtools::wall::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+// JSONに変換して戻したときに元の値と一致するか確認
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+    let json = serde_json::to_string(value).unwrap();
+    let decoded: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&decoded, value, "json: {}", json);
+}
+
+#[test]
+fn test_tile_encoding() {
+    assert_eq!(serde_json::to_string(&TileId::Id5man).unwrap(), r#""5m""#);
+    assert_eq!(serde_json::to_string(&TileId::IdChun).unwrap(), r#""7z""#);
+    assert_eq!(
+        serde_json::to_string(&Tile {
+            id: TileId::Id5pin,
+            aka: true
+        })
+        .unwrap(),
+        r#""0p""#
+    );
+    assert_eq!(serde_json::to_string(&Wind::Nan).unwrap(), r#""nan""#);
+    assert_eq!(
+        serde_json::from_str::<Tile>(r#""0s""#).unwrap(),
+        Tile {
+            id: TileId::Id5sou,
+            aka: true
+        }
+    );
+    assert!(serde_json::from_str::<Tile>(r#""5x""#).is_err());
+    assert!(serde_json::from_str::<TileId>(r#""12m""#).is_err());
+
+    for t in TileId::ALL {
+        assert_round_trip(&t);
+    }
+}
+
+#[test]
+fn test_hand_round_trip() {
+    let hand = Hand::from_compactstr("234m067p11z[111z](9999s)").unwrap();
+    assert_round_trip(&hand);
+    for meld in &hand.melds {
+        assert_round_trip(meld);
+    }
+    assert_round_trip(&"1m2p/3s".parse::<Dora>().unwrap());
+
+    let json = serde_json::to_string(&hand.melds[0]).unwrap();
+    assert_eq!(json, r#"{"Pung":{"tiles":["1z","1z","1z"]}}"#);
+
+    // 副露元と加槓で加えた牌
+    let hand = Hand::from_compactstr("234m067p11z[<567s][^999+9s]").unwrap();
+    assert_round_trip(&hand);
+    let json = serde_json::to_string(&hand.melds[1]).unwrap();
+    assert_eq!(
+        json,
+        r#"{"Kakan":{"tiles":["9s","9s","9s","9s"],"from":"Toimen","added":"9s"}}"#
+    );
+}
+
+#[test]
+fn test_score_round_trip() {
+    let info = AgariInformation {
+        wining_tile: "3s".parse().unwrap(),
+        hand: Hand::from_compactstr("123m456p067s45s99m").unwrap(),
+        nhonba: 1,
+        nriichi: 0,
+        round: Wind::Ton,
+        player: Wind::Nan,
+        tsumo: true,
+        riichi: true,
+        ippatsu: false,
+        doubleriichi: false,
+        haitei: false,
+        rinshan: false,
+        chankan: false,
+        nagashimangan: false,
+        tenho: false,
+        chiho: false,
+        dora: "1m/2p".parse().unwrap(),
+    };
+    assert_round_trip(&info);
+
+    let score = calculate_score(&info).unwrap();
+    assert_round_trip(&score);
+    assert_round_trip(&score.point.feed);
+    for (yaku, _) in &score.yaku {
+        assert_round_trip(yaku);
+    }
+}
+
+#[test]
+fn test_rule_config() {
+    let config = ScoreRuleConfig {
+        kuitan: false,
+        ..Default::default()
+    };
+    assert_round_trip(&config);
+
+    // 省略した項目は既定値になる
+    let partial: ScoreRuleConfig = serde_json::from_str(r#"{"kuitan": false}"#).unwrap();
+    assert_eq!(partial, config);
+}
+
+#[test]
+fn test_wall_round_trip() {
+    let mut wall = Wall::new(&WallConfig::default(), 42).unwrap();
+    assert_round_trip(&wall);
+    wall.deal().unwrap();
+    wall.draw_rinshan().unwrap();
+    wall.flip_dora().unwrap();
+    assert_round_trip(&wall);
+
+    // 牌の構成や自摸の状態が不正なものは読み込めない
+    let json = serde_json::to_value(&wall).unwrap();
+    let modified = |key: &str, value: serde_json::Value| {
+        let mut json = json.clone();
+        json[key] = value;
+        serde_json::from_value::<Wall>(json)
+    };
+    assert!(modified("tiles", serde_json::json!(vec!["1m"; 136])).is_err());
+    assert!(modified("num_drawn", serde_json::json!(1000)).is_err());
+    assert!(modified("num_rinshan", serde_json::json!(5)).is_err());
+    assert!(modified("num_dora", serde_json::json!(0)).is_err());
+    assert!(modified("num_dora", serde_json::json!(6)).is_err());
+    assert!(modified("num_drawn", serde_json::json!(118)).is_ok());
+}
+
+#[test]
+fn test_game_rejects_agari_without_winner() {
+    let mut game = Game::new(&GameConfig::default());
+    let mut json = serde_json::to_value(game.start_kyoku(0).unwrap()).unwrap();
+    json["result"] = serde_json::json!({ "Agari": [] });
+    let kyoku: Kyoku = serde_json::from_value(json).unwrap();
+    assert!(game.apply_kyoku_result(&kyoku).is_err());
+    assert_eq!(game.scores(), [25000; 4]);
+}
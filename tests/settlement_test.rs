@@ -0,0 +1,180 @@
+mod common;
+
+use common::*;
+use mjtools::score::*;
+use mjtools::settlement::*;
+use mjtools::types::*;
+
+// 河を作る（calledに含まれる添字の牌は鳴かれたものとする）
+fn discards(s: &str, called: &[usize]) -> Vec<DiscardedTile> {
+    Tile::from_compactstr(s)
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(i, tile)| DiscardedTile {
+            tile,
+            tsumogiri: false,
+            riichi: false,
+            called: called.contains(&i),
+        })
+        .collect()
+}
+
+#[test]
+fn test_noten_penalty() {
+    assert_eq!(calculate_noten_penalty([false; 4]), [0; 4]);
+    assert_eq!(calculate_noten_penalty([true; 4]), [0; 4]);
+    assert_eq!(
+        calculate_noten_penalty([true, false, false, false]),
+        [3000, -1000, -1000, -1000]
+    );
+    assert_eq!(
+        calculate_noten_penalty([true, false, true, false]),
+        [1500, -1500, 1500, -1500]
+    );
+    assert_eq!(
+        calculate_noten_penalty([false, true, true, true]),
+        [-3000, 1000, 1000, 1000]
+    );
+}
+
+#[test]
+fn test_nagashimangan() {
+    assert!(is_nagashimangan(&discards("19m1p9s1234567z", &[])));
+    // 空の河、中張牌を含む、鳴かれた
+    assert!(!is_nagashimangan(&[]));
+    assert!(!is_nagashimangan(&discards("19m2p9s1234567z", &[])));
+    assert!(!is_nagashimangan(&discards("19m1p9s1234567z", &[3])));
+
+    let rule = ScoreRuleConfig::default();
+    let nagashi = discards("19m1p9s1234567z", &[]);
+    let normal = discards("19m2p9s1234567z", &[]);
+
+    // 子の流し満貫は不聴罰符より優先
+    let settlement = settle_exhaustive_draw(
+        [true, false, false, false],
+        [&normal, &nagashi, &normal, &normal],
+        0,
+        &rule,
+    );
+    assert_eq!(settlement.nagashimangan, vec![1]);
+    assert_eq!(settlement.deltas, [-4000, 8000, -2000, -2000]);
+
+    // 親と子の流し満貫
+    let settlement =
+        settle_exhaustive_draw([false; 4], [&nagashi, &normal, &nagashi, &normal], 0, &rule);
+    assert_eq!(settlement.nagashimangan, vec![0, 2]);
+    assert_eq!(
+        settlement.deltas,
+        [12000 - 4000, -4000 - 2000, 8000 - 4000, -4000 - 2000]
+    );
+    assert_eq!(settlement.deltas.iter().sum::<i32>(), 0);
+
+    // 流し満貫なしのルールでは不聴罰符
+    let settlement = settle_exhaustive_draw(
+        [true, false, false, false],
+        [&normal, &nagashi, &normal, &normal],
+        0,
+        &ScoreRuleConfig {
+            nagashimangan: false,
+            ..rule
+        },
+    );
+    assert!(settlement.nagashimangan.is_empty());
+    assert_eq!(settlement.deltas, [3000, -1000, -1000, -1000]);
+}
+
+#[test]
+fn test_multiple_ron() {
+    let rule = ScoreRuleConfig::default();
+    // 2本場で計算した得点
+    let (r3, r1, r2) = (ron(8000 + 600), ron(2000 + 600), ron(12000 + 600));
+    let wins = [(3, &r3), (1, &r1), (2, &r2)];
+
+    // ダブロン/トリロン: 本場と供託は放銃者の下家から最も近い和了者
+    let settlement = settle_ron(0, &wins, 2, 1, &rule, MultipleRonRule::MultipleRon, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(settlement.winners, vec![1, 2, 3]);
+    assert_eq!(
+        settlement.deltas,
+        [-(2000 + 600 + 12000 + 8000), 2000 + 600 + 1000, 12000, 8000]
+    );
+
+    // 放銃者が親以外でも下家から順
+    let settlement = settle_ron(
+        2,
+        &wins[..2],
+        2,
+        0,
+        &rule,
+        MultipleRonRule::MultipleRon,
+        true,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(settlement.winners, vec![3, 1]);
+    assert_eq!(settlement.deltas, [0, 2000, -(8000 + 600 + 2000), 8600]);
+
+    // 頭ハネ
+    let settlement = settle_ron(0, &wins, 2, 2, &rule, MultipleRonRule::Atamahane, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(settlement.winners, vec![1]);
+    assert_eq!(settlement.deltas, [-2600, 2600 + 2000, 0, 0]);
+
+    // 三家和
+    assert_eq!(
+        settle_ron(0, &wins, 2, 0, &rule, MultipleRonRule::MultipleRon, true).unwrap(),
+        None
+    );
+    assert_eq!(
+        select_ron_winners(
+            0,
+            [false, true, true, true],
+            MultipleRonRule::Atamahane,
+            true
+        ),
+        None
+    );
+
+    // 不正な席
+    assert!(settle_ron(0, &[], 0, 0, &rule, MultipleRonRule::MultipleRon, true).is_err());
+    assert!(settle_ron(
+        0,
+        &[(0, &ron(1000))],
+        0,
+        0,
+        &rule,
+        MultipleRonRule::MultipleRon,
+        true
+    )
+    .is_err());
+    assert!(settle_ron(
+        0,
+        &[(1, &ron(1000)), (1, &ron(1000))],
+        0,
+        0,
+        &rule,
+        MultipleRonRule::MultipleRon,
+        true
+    )
+    .is_err());
+
+    // 自摸の得点
+    let mut tsumo = ron(3000);
+    tsumo.point.feed = Feed::Tsumo {
+        ko: 1000,
+        oya: 1000,
+    };
+    assert!(settle_ron(
+        0,
+        &[(1, &tsumo)],
+        0,
+        0,
+        &rule,
+        MultipleRonRule::MultipleRon,
+        true
+    )
+    .is_err());
+}
@@ -64,7 +64,7 @@ fn test_shanten_problems() {
                 entry[0..14]
                     .iter()
                     .fold(Vec::<TileId>::new(), |mut vec, t| {
-                        vec.push(*tileidmap.get(&t).unwrap());
+                        vec.push(*tileidmap.get(t).unwrap());
                         vec
                     });
             let normal_answer = entry[14];
@@ -109,6 +109,12 @@ fn test_effective_tiles() {
         );
     }
 
+    // 4枚持っている牌は有効牌にならない
+    let quad = TileId::from_tilestr("🀇🀇🀇🀇🀈🀜🀝🀞🀖🀗🀘🀀🀀").unwrap();
+    assert!(!listup_effective_tiles(&quad)
+        .unwrap()
+        .contains(&TileId::Id1man));
+
     for case in chitoitsu_tests {
         assert_eq!(
             listup_chitoitsu_effective_tiles(TileId::from_tilestr(case.0).unwrap().as_slice())
@@ -125,3 +131,250 @@ fn test_effective_tiles() {
         );
     }
 }
+
+#[test]
+fn test_count_effective_tiles() {
+    let construct_hand = |s: &str| Hand {
+        hand: Tile::from_mjscorestr(s).unwrap(),
+        melds: vec![],
+    };
+    let no_dora = Dora {
+        omote: vec![],
+        ura: vec![],
+    };
+
+    // 手牌の牌だけを除く
+    let hand = construct_hand("5m6m7m2p3p4p3s3s5s6s7s7s8s");
+    assert_eq!(
+        count_effective_tiles(&hand, &no_dora, &[]).unwrap(),
+        vec![(TileId::Id6sou, 3), (TileId::Id9sou, 4)]
+    );
+
+    // 表ドラ表示牌と見えている牌も除く（裏ドラは数えない）
+    let dora = Dora {
+        omote: Tile::from_mjscorestr("9s").unwrap(),
+        ura: Tile::from_mjscorestr("6s").unwrap(),
+    };
+    let visible = TileId::from_tilestr("🀕🀕🀘").unwrap();
+    assert_eq!(
+        count_effective_tiles(&hand, &dora, &visible).unwrap(),
+        vec![(TileId::Id6sou, 1), (TileId::Id9sou, 2)]
+    );
+
+    // 全て見えている有効牌は0枚
+    let visible = TileId::from_tilestr("🀕🀕🀕").unwrap();
+    assert_eq!(
+        count_effective_tiles(&hand, &dora, &visible).unwrap(),
+        vec![(TileId::Id6sou, 0), (TileId::Id9sou, 3)]
+    );
+
+    // 5枚以上見えている
+    let visible = TileId::from_tilestr("🀖🀖🀖🀖").unwrap();
+    assert!(count_effective_tiles(&hand, &no_dora, &visible).is_err());
+}
+
+#[test]
+fn test_analyze_discards() {
+    let no_dora = Dora {
+        omote: vec![],
+        ura: vec![],
+    };
+
+    // 浮いた字牌を切れば聴牌
+    let hand = Hand {
+        hand: Tile::from_mjscorestr("5M6m7m2p3p4p3s3s5s6s7s7s8s北").unwrap(),
+        melds: vec![],
+    };
+    let candidates = analyze_discards(&hand, &no_dora, &[]).unwrap();
+    assert_eq!(candidates.len(), 12);
+    assert_eq!(
+        candidates[0],
+        DiscardCandidate {
+            discard: TileId::IdPee,
+            shanten: 0,
+            effective_tiles: vec![(TileId::Id6sou, 3), (TileId::Id9sou, 4)],
+        }
+    );
+    // 向聴数が小さい順、有効牌が多い順に並ぶ
+    for pair in candidates.windows(2) {
+        assert!(
+            (pair[0].shanten, -pair[0].num_effective_tiles())
+                <= (pair[1].shanten, -pair[1].num_effective_tiles())
+        );
+    }
+    // 見えている牌の分だけ有効牌が減る
+    let discard_7s = |candidates: &[DiscardCandidate]| {
+        candidates
+            .iter()
+            .find(|c| c.discard == TileId::Id7sou)
+            .unwrap()
+            .num_effective_tiles()
+    };
+    let visible = TileId::from_tilestr("🀓🀓🀓🀓").unwrap();
+    assert_eq!(
+        discard_7s(&analyze_discards(&hand, &no_dora, &visible).unwrap()),
+        discard_7s(&candidates) - 4
+    );
+
+    // 打牌した牌はvisibleとは別に数える
+    let hand = Hand {
+        hand: Tile::from_mjscorestr("1m2m3m4m5m6m7m8m9m1p2p3p5p5p").unwrap(),
+        melds: vec![],
+    };
+    let discard_5p = |visible: &[TileId]| {
+        analyze_discards(&hand, &no_dora, visible)
+            .unwrap()
+            .into_iter()
+            .find(|c| c.discard == TileId::Id5pin)
+            .unwrap()
+            .effective_tiles
+    };
+    assert_eq!(discard_5p(&[]), vec![(TileId::Id5pin, 2)]);
+    assert_eq!(discard_5p(&[TileId::Id5pin]), vec![(TileId::Id5pin, 1)]);
+    assert!(analyze_discards(&hand, &no_dora, &[TileId::Id5pin; 3]).is_err());
+
+    // 牌数が不正
+    let hand = Hand {
+        hand: Tile::from_mjscorestr("5m6m7m2p3p4p3s3s5s6s7s7s8s").unwrap(),
+        melds: vec![],
+    };
+    assert!(analyze_discards(&hand, &no_dora, &[]).is_err());
+}
+
+#[test]
+fn test_evaluate_ukeire() {
+    let no_dora = Dora {
+        omote: vec![],
+        ura: vec![],
+    };
+    let hand = Hand {
+        hand: Tile::from_mjscorestr("1m2m3m4m5m6m7m8m9m1p1s3s東").unwrap(),
+        melds: vec![],
+    };
+    let evaluation = evaluate_ukeire(&hand, &no_dora, &[]).unwrap();
+    assert_eq!(evaluation.shanten, 1);
+    assert_eq!(
+        evaluation.effective_tiles,
+        count_effective_tiles(&hand, &no_dora, &[]).unwrap()
+    );
+    let current_ukeire: i32 = evaluation.effective_tiles.iter().map(|(_, n)| n).sum();
+    // 改良牌は有効牌ではなく、有効牌を増やす
+    assert!(!evaluation.improving_tiles.is_empty());
+    for (t, remain, ukeire) in &evaluation.improving_tiles {
+        assert!(evaluation.effective_tiles.iter().all(|(e, _)| e != t));
+        assert!(*remain > 0 && *ukeire > current_ukeire);
+    }
+    assert!(evaluation.expected_ukeire > 0.0);
+
+    // 8p切りと4p切りはどちらも受け入れ12枚だが、次の自摸後の受け入れは8p切りが多い
+    // 見えていない牌は122枚（手牌13枚と打牌1枚を除く）
+    let after = |discard: TileId| {
+        let mut hand = Hand {
+            hand: Tile::from_mjscorestr("2m3m5m6m7m2p3p4p4p6p8p1s1s9s").unwrap(),
+            melds: vec![],
+        };
+        let i = hand.hand.iter().position(|t| t.id == discard).unwrap();
+        hand.hand.remove(i);
+        evaluate_ukeire(&hand, &no_dora, &[discard]).unwrap()
+    };
+    let (e8p, e4p) = (after(TileId::Id8pin), after(TileId::Id4pin));
+    assert_eq!(
+        e8p.effective_tiles,
+        vec![
+            (TileId::Id1man, 4),
+            (TileId::Id4man, 4),
+            (TileId::Id5pin, 4)
+        ]
+    );
+    assert_eq!(
+        e4p.effective_tiles,
+        vec![
+            (TileId::Id1man, 4),
+            (TileId::Id4man, 4),
+            (TileId::Id7pin, 4)
+        ]
+    );
+    assert_eq!(e8p.expected_ukeire, 1522.0 / 122.0);
+    assert_eq!(e4p.expected_ukeire, 1504.0 / 122.0);
+    // 1sを自摸すると、8p切りは受け入れ27枚、4p切りは24枚の一向聴に改良できる
+    assert!(e8p.improving_tiles.contains(&(TileId::Id1sou, 2, 27)));
+    assert!(e4p.improving_tiles.contains(&(TileId::Id1sou, 2, 24)));
+
+    // 聴牌の手は評価しない
+    let hand = Hand {
+        hand: Tile::from_mjscorestr("1m2m3m4m5m6m7m8m9m1p1p1s2s").unwrap(),
+        melds: vec![],
+    };
+    assert!(evaluate_ukeire(&hand, &no_dora, &[]).is_err());
+}
+
+#[test]
+fn test_shanten_with_melds() {
+    let pung = |s: &str| {
+        let t = Tile::from_mjscorestr(s).unwrap()[0];
+        Meld::Pung {
+            tiles: [t, t, t],
+            from: None,
+        }
+    };
+    let construct_hand = |s: &str, melds: Vec<Meld>| Hand {
+        hand: Tile::from_mjscorestr(s).unwrap(),
+        melds,
+    };
+
+    // 1副露の聴牌
+    let hand = construct_hand("1m2m3m4p5p6p7s8s東東", vec![pung("白")]);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), 0);
+    assert_eq!(
+        listup_hand_effective_tiles(&hand).unwrap(),
+        HashSet::from([TileId::Id6sou, TileId::Id9sou])
+    );
+    // 純手牌だけでも副露数を補って計算
+    let pure_tiles: Vec<TileId> = hand.hand.iter().map(|t| t.id).collect();
+    assert_eq!(calculate_normal_shanten(&pure_tiles), 0);
+    assert_eq!(calculate_shanten(&pure_tiles), 0);
+
+    // 1副露の一向聴
+    let hand = construct_hand("1m2m3m4p5p6p7s8s東南", vec![pung("白")]);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), 1);
+
+    // 副露していれば七対子/国士無双は考慮しない
+    let hand = construct_hand("1m1m9m9m1p1p9p9p1s東", vec![pung("白")]);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), 2);
+    assert!(!listup_hand_effective_tiles(&hand)
+        .unwrap()
+        .contains(&TileId::Id1sou));
+    let hand = construct_hand("1m9m1p9p1s9s東南西北", vec![pung("白")]);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), 6);
+
+    // 4副露の裸単騎
+    let melds = vec![pung("1m"), pung("2p"), pung("3s"), pung("発")];
+    let hand = construct_hand("東", melds);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), 0);
+    assert_eq!(
+        listup_hand_effective_tiles(&hand).unwrap(),
+        HashSet::from([TileId::IdTon])
+    );
+    let melds = vec![pung("1m"), pung("2p"), pung("3s"), pung("発")];
+    let hand = construct_hand("東東", melds);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), -1);
+
+    // 2副露の自摸後
+    let hand = construct_hand("2m3m4m5p5p7s8s9s", vec![pung("白"), pung("中")]);
+    assert_eq!(calculate_hand_shanten(&hand).unwrap(), -1);
+
+    // 副露数と純手牌の枚数が合わない
+    let hand = construct_hand("1m2m3m4p5p6p7s8s東東", vec![]);
+    assert!(calculate_hand_shanten(&hand).is_err());
+    assert!(listup_hand_effective_tiles(&hand).is_err());
+
+    // 同じ牌が5枚以上（副露を含む）
+    let hand = construct_hand("1m1m1m1m1m2m2m2m3m3m3m4m4m", vec![]);
+    assert!(calculate_hand_shanten(&hand).is_err());
+    assert!(listup_hand_effective_tiles(&hand).is_err());
+    let hand = construct_hand("東東2m2m2m3m3m3m4m4m", vec![pung("東")]);
+    assert!(calculate_hand_shanten(&hand).is_err());
+    let tiles = TileId::from_tilestr("🀀🀀🀀🀀🀀🀈🀈🀈🀉🀉🀉🀊🀊").unwrap();
+    assert!(listup_effective_tiles(&tiles).is_err());
+    assert_eq!(calculate_shanten(&tiles), 0);
+}
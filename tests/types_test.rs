@@ -248,3 +248,514 @@ fn test_from_invalid_mjscorestr() {
     Tile::from_mjscorestr("發").unwrap();
     Tile::from_mjscorestr("1").unwrap();
 }
+
+#[test]
+fn test_from_compactstr() {
+    let tile = |id: TileId, aka: bool| Tile { id, aka };
+    assert_eq!(
+        Tile::from_compactstr("123m406p789s1234567z").unwrap(),
+        [
+            tile(TileId::Id1man, false),
+            tile(TileId::Id2man, false),
+            tile(TileId::Id3man, false),
+            tile(TileId::Id4pin, false),
+            tile(TileId::Id5pin, true),
+            tile(TileId::Id6pin, false),
+            tile(TileId::Id7sou, false),
+            tile(TileId::Id8sou, false),
+            tile(TileId::Id9sou, false),
+            tile(TileId::IdTon, false),
+            tile(TileId::IdNan, false),
+            tile(TileId::IdSha, false),
+            tile(TileId::IdPee, false),
+            tile(TileId::IdHaku, false),
+            tile(TileId::IdHatu, false),
+            tile(TileId::IdChun, false),
+        ]
+    );
+    // mjscoreの牌表示も受け付ける
+    assert_eq!(
+        Tile::from_compactstr("5M6m7m3p4p東白").unwrap(),
+        Tile::from_mjscorestr("5M6m7m3p4p東白").unwrap()
+    );
+    assert_eq!(
+        Tile::from_compactstr("067m34p15z").unwrap(),
+        Tile::from_mjscorestr("5M6m7m3p4p東白").unwrap()
+    );
+    // 不正な表記
+    for s in ["123", "m", "8z", "0z", "1M", "1m東2", "發"] {
+        assert!(Tile::from_compactstr(s).is_err(), "{} must be invalid", s);
+    }
+}
+
+#[test]
+fn test_compactstr_round_trip() {
+    // 全ての牌で簡易表記とmjscoreの牌表示が相互に変換できる
+    for id in TileId::ALL {
+        for aka in [false, true] {
+            if aka && !(id.is_suhai() && id.getnumber() == 5) {
+                continue;
+            }
+            let tile = Tile { id, aka };
+            let compact = Tile::to_compactstr(&[tile]);
+            assert_eq!(Tile::from_compactstr(&compact).unwrap(), [tile]);
+            assert_eq!(
+                Tile::from_compactstr(&tile.to_mjscorestr()).unwrap(),
+                [tile]
+            );
+            assert_eq!(
+                Tile::from_mjscorestr(&tile.to_mjscorestr()).unwrap(),
+                Tile::from_compactstr(&compact).unwrap()
+            );
+        }
+    }
+    // 同じ種類が続く数字はまとめる
+    let tiles = Tile::from_mjscorestr("1m2m3m5P4p6p東東白1s").unwrap();
+    assert_eq!(Tile::to_compactstr(&tiles), "123m046p115z1s");
+    assert_eq!(
+        Tile::from_compactstr(&Tile::to_compactstr(&tiles)).unwrap(),
+        tiles
+    );
+}
+
+#[test]
+fn test_hand_from_compactstr() {
+    let hand = Hand::from_compactstr("123m406p11z[789s](5555z)[4<44p][0^55m]").unwrap();
+    assert_eq!(hand.hand, Tile::from_compactstr("123m406p11z").unwrap());
+    assert_eq!(hand.melds.len(), 4);
+    assert!(matches!(
+        hand.melds[0],
+        Meld::Chow { tiles, .. } if tiles[0].id == TileId::Id7sou
+    ));
+    assert!(matches!(
+        hand.melds[1],
+        Meld::Ankan { tiles } if tiles[0].id == TileId::IdHaku
+    ));
+    assert!(matches!(
+        hand.melds[2],
+        Meld::Pung { tiles, .. } if tiles[0].id == TileId::Id4pin
+    ));
+    assert!(matches!(
+        hand.melds[3],
+        Meld::Pung { tiles, .. } if tiles[0].aka && tiles[0].id == TileId::Id5man
+    ));
+    assert_eq!(hand.melds[2].called_from(), Some(CalledFrom::Kamicha));
+    assert_eq!(hand.melds[3].called_from(), Some(CalledFrom::Toimen));
+    assert_eq!(
+        hand.to_compactstr(),
+        "123m406p11z[789s](5555z)[<444p][^055m]"
+    );
+
+    // チーは順不同で指定できる
+    let hand = Hand::from_compactstr("11z[312m][5555p]").unwrap();
+    assert!(matches!(
+        hand.melds[0],
+        Meld::Chow { tiles, .. } if tiles[0].id == TileId::Id1man && tiles[2].id == TileId::Id3man
+    ));
+    assert!(matches!(hand.melds[1], Meld::Minkan { .. }));
+    assert_eq!(hand.to_compactstr(), "11z[123m][5555p]");
+
+    // 不正な副露
+    for s in [
+        "11z[124m]",
+        "11z[123z]",
+        "11z(123m)",
+        "11z[55m]",
+        "11z[555m",
+        "11z(555m)",
+    ] {
+        assert!(Hand::from_compactstr(s).is_err(), "{} must be invalid", s);
+    }
+}
+
+#[test]
+fn test_to_char_round_trip() {
+    for id in TileId::ALL {
+        assert_eq!(TileId::from_char(id.to_char()).unwrap(), id);
+    }
+    // 全ての牌の文字が異なる
+    let tilestr = TileId::to_tilestr(&TileId::ALL);
+    assert_eq!(tilestr.chars().count(), 34);
+    assert_eq!(TileId::from_tilestr(&tilestr).unwrap(), TileId::ALL);
+    assert_eq!(
+        TileId::to_tilestr(&[TileId::Id1man, TileId::Id2man, TileId::Id3man]),
+        "🀇🀈🀉"
+    );
+    assert_eq!(
+        TileId::to_tilestr(&TileId::from_tilestr("🀐🀙🀀🀆🀅🀄").unwrap()),
+        "🀐🀙🀀🀆🀅🀄"
+    );
+}
+
+#[test]
+fn test_parse_error() {
+    // 不正な入力でもパニックせず、位置を報告する
+    assert_eq!(
+        Tile::from_mjscorestr("1m2").unwrap_err(),
+        ParseError::MissingSuit { pos: 2 }
+    );
+    assert_eq!(
+        Tile::from_mjscorestr("1m2x").unwrap_err(),
+        ParseError::UnexpectedChar { pos: 3, c: 'x' }
+    );
+    assert_eq!(
+        Tile::from_mjscorestr("東發").unwrap_err(),
+        ParseError::UnexpectedChar { pos: 1, c: '發' }
+    );
+    assert_eq!(
+        Tile::from_compactstr("123m45").unwrap_err(),
+        ParseError::MissingSuit { pos: 4 }
+    );
+    assert_eq!(
+        Tile::from_compactstr("12p3東").unwrap_err(),
+        ParseError::MissingSuit { pos: 3 }
+    );
+    assert_eq!(
+        Tile::from_compactstr("11zp").unwrap_err(),
+        ParseError::MissingNumber { pos: 3, c: 'p' }
+    );
+    assert_eq!(
+        Tile::from_compactstr("1238z").unwrap_err(),
+        ParseError::NoSuchTile {
+            pos: 3,
+            tile: "8z".to_string()
+        }
+    );
+    assert_eq!(
+        TileId::from_tilestr("🀇🀈x").unwrap_err(),
+        ParseError::UnexpectedChar { pos: 2, c: 'x' }
+    );
+    assert_eq!(
+        Hand::from_compactstr("123m[456p").unwrap_err(),
+        ParseError::UnclosedGroup { pos: 4 }
+    );
+    assert_eq!(
+        Hand::from_compactstr("123m[456p](12z)").unwrap_err(),
+        ParseError::InvalidMeld { pos: 10 }
+    );
+    assert_eq!(
+        Hand::from_compactstr("123m[45xp]").unwrap_err(),
+        ParseError::UnexpectedChar { pos: 7, c: 'x' }
+    );
+    assert_eq!(
+        "1m2m".parse::<Tile>().unwrap_err(),
+        ParseError::TileCount {
+            expected: 1,
+            found: 2
+        }
+    );
+    assert_eq!(
+        "foo".parse::<Wind>().unwrap_err().to_string(),
+        "unexpected word 'foo' at 0"
+    );
+    assert_eq!(
+        Tile::from_compactstr("123m45").unwrap_err().to_string(),
+        "missing suit after number at 4"
+    );
+}
+
+#[test]
+fn test_display_from_str() {
+    // 牌
+    for id in TileId::ALL {
+        assert_eq!(id.to_string().parse::<TileId>().unwrap(), id);
+        let tile = Tile { id, aka: false };
+        assert_eq!(tile.to_string().parse::<Tile>().unwrap(), tile);
+    }
+    assert_eq!(TileId::IdChun.to_string(), "7z");
+    let aka: Tile = "0p".parse().unwrap();
+    assert_eq!(
+        aka,
+        Tile {
+            id: TileId::Id5pin,
+            aka: true
+        }
+    );
+    assert_eq!(aka.to_string(), "0p");
+    assert_eq!("東".parse::<TileId>().unwrap(), TileId::IdTon);
+
+    // 副露
+    for s in ["[123m]", "[555z]", "[0555s]", "(1111p)"] {
+        assert_eq!(s.parse::<Meld>().unwrap().to_string(), s);
+    }
+    assert!("123m".parse::<Meld>().is_err());
+    assert!("[123m][456m]".parse::<Meld>().is_err());
+
+    // 手牌
+    let hand: Hand = "123m406p11z[789s](5555z)".parse().unwrap();
+    assert_eq!(hand.to_string(), "123m406p11z[789s](5555z)");
+    assert_eq!(hand.to_string().parse::<Hand>().unwrap(), hand);
+
+    // ドラ表示牌
+    let dora: Dora = "1m5z/3p".parse().unwrap();
+    assert_eq!(dora.omote, Tile::from_compactstr("1m5z").unwrap());
+    assert_eq!(dora.ura, Tile::from_compactstr("3p").unwrap());
+    assert_eq!(dora.to_string(), "1m5z/3p");
+    let dora: Dora = "0s".parse().unwrap();
+    assert!(dora.ura.is_empty());
+    assert_eq!(dora.to_string(), "0s");
+    assert_eq!(
+        "1m/3x".parse::<Dora>().unwrap_err(),
+        ParseError::UnexpectedChar { pos: 4, c: 'x' }
+    );
+
+    // 風
+    for wind in [Wind::Ton, Wind::Nan, Wind::Sha, Wind::Pee] {
+        assert_eq!(wind.to_string().parse::<Wind>().unwrap(), wind);
+    }
+    assert_eq!("NAN".parse::<Wind>().unwrap(), Wind::Nan);
+    assert_eq!("西".parse::<Wind>().unwrap(), Wind::Sha);
+}
+
+#[test]
+fn test_hand_builder() {
+    let tiles = |s: &str| Tile::from_compactstr(s).unwrap();
+
+    // 副露の牌の並びから構築
+    let hand = HandBuilder::new()
+        .tiles(&tiles("067m34p33s"))
+        .chow(&tiles("657s"))
+        .pung(&tiles("888s"))
+        .wining_tile(tiles("2p")[0])
+        .build()
+        .unwrap();
+    assert_eq!(hand, "067m34p33s[567s][888s]".parse().unwrap());
+
+    // 槓子は3枚と数える
+    let hand = HandBuilder::new()
+        .tiles(&tiles("067m34p33s"))
+        .ankan(&tiles("9999s"))
+        .minkan(&tiles("1111z"))
+        .build()
+        .unwrap();
+    assert_eq!(hand.melds[0].tiles(), &tiles("9999s")[..]);
+    assert!(matches!(hand.melds[1], Meld::Minkan { .. }));
+
+    // 副露の形
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33567s"))
+            .pung(&tiles("887s"))
+            .build()
+            .unwrap_err(),
+        HandError::InvalidMeld {
+            tiles: tiles("887s")
+        }
+    );
+    assert!(HandBuilder::new()
+        .tiles(&tiles("067m34p33567s"))
+        .chow(&tiles("135s"))
+        .build()
+        .is_err());
+    assert!(HandBuilder::new()
+        .tiles(&tiles("067m34p33567s"))
+        .ankan(&tiles("888s"))
+        .build()
+        .is_err());
+    // 直接構成した副露も検証する
+    assert!(HandBuilder::new()
+        .tiles(&tiles("067m34p33567s"))
+        .meld(Meld::Pung {
+            tiles: [tiles("1m")[0], tiles("2m")[0], tiles("3m")[0]],
+            from: None,
+        })
+        .build()
+        .is_err());
+
+    // 枚数
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33567s"))
+            .wining_tile(tiles("2p")[0])
+            .build()
+            .unwrap_err(),
+        HandError::TileCount {
+            expected: 14,
+            found: 11
+        }
+    );
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33567s"))
+            .pung(&tiles("888s"))
+            .pung(&tiles("999s"))
+            .build()
+            .unwrap_err(),
+        HandError::TileCount {
+            expected: 14,
+            found: 16
+        }
+    );
+
+    // 同じ牌は4枚まで（槓子の4枚目と和了牌を含む）
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33567s"))
+            .ankan(&tiles("7777s"))
+            .build()
+            .unwrap_err(),
+        HandError::TooManyCopies {
+            tile: TileId::Id7sou,
+            found: 5
+        }
+    );
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m22p33567s"))
+            .pung(&tiles("222p"))
+            .wining_tile(tiles("2p")[0])
+            .build()
+            .unwrap_err(),
+        HandError::TooManyCopies {
+            tile: TileId::Id2pin,
+            found: 6
+        }
+    );
+
+    // 赤5は種類ごとに1枚まで
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33567s"))
+            .pung(&tiles("550m"))
+            .build()
+            .unwrap_err(),
+        HandError::TooManyAka {
+            tile: TileId::Id5man,
+            found: 2
+        }
+    );
+    assert!(HandBuilder::new()
+        .tiles(&tiles("067m34p33567s"))
+        .pung(&tiles("550p"))
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn test_called_from_and_kakan() {
+    let tiles = |s: &str| Tile::from_compactstr(s).unwrap();
+
+    // 加槓は加えた牌の前に+を書く
+    let hand = Hand::from_compactstr("123m406p11z[^555+0m][<1111z][>2222z]").unwrap();
+    assert_eq!(
+        hand.melds[0],
+        Meld::Kakan {
+            tiles: [
+                tiles("5m")[0],
+                tiles("5m")[0],
+                tiles("5m")[0],
+                tiles("0m")[0]
+            ],
+            from: Some(CalledFrom::Toimen),
+            added: tiles("0m")[0],
+        }
+    );
+    assert!(matches!(
+        hand.melds[1],
+        Meld::Minkan {
+            from: Some(CalledFrom::Kamicha),
+            ..
+        }
+    ));
+    assert_eq!(hand.melds[2].called_from(), Some(CalledFrom::Shimocha));
+    assert_eq!(hand.to_compactstr(), "123m406p11z[^555+0m][<1111z][>2222z]");
+    assert_eq!(
+        Hand::from_compactstr("11z[111+1z]")
+            .unwrap()
+            .to_compactstr(),
+        "11z[111+1z]"
+    );
+    // 加えた牌がtilesの最後でなくても、加えた牌を+の後に書く
+    let hand = Hand {
+        hand: tiles("11z"),
+        melds: vec![Meld::Kakan {
+            tiles: [
+                tiles("0m")[0],
+                tiles("5m")[0],
+                tiles("5m")[0],
+                tiles("5m")[0],
+            ],
+            from: None,
+            added: tiles("0m")[0],
+        }],
+    };
+    assert_eq!(hand.to_compactstr(), "11z[555+0m]");
+    let parsed = Hand::from_compactstr(&hand.to_compactstr()).unwrap();
+    assert_eq!(parsed.melds[0].tiles().iter().filter(|t| t.aka).count(), 1);
+
+    // 不正な副露元の記号と加槓
+    for s in [
+        "11z[<^123m]",
+        "11z(<1111p)",
+        "11z[55+5m]",
+        "11z[555++5m]",
+        "11z(555+5m)",
+    ] {
+        assert!(
+            matches!(
+                Hand::from_compactstr(s),
+                Err(ParseError::InvalidMeld { .. })
+            ),
+            "{}",
+            s
+        );
+    }
+
+    // チーは上家からのみ
+    assert!(Hand::from_compactstr("067m34p33567s[<888s]")
+        .unwrap()
+        .validate(Some(tiles("2p")[0]))
+        .is_ok());
+    assert!(Hand::from_compactstr("067m34p33888s[^567s]")
+        .unwrap()
+        .validate(Some(tiles("2p")[0]))
+        .is_err());
+
+    // 手牌の構築
+    let hand = HandBuilder::new()
+        .tiles(&tiles("067m34p33s"))
+        .pung(&tiles("888s"))
+        .called_from(CalledFrom::Toimen)
+        .kakan(&tiles("5550p"))
+        .called_from(CalledFrom::Shimocha)
+        .wining_tile(tiles("2p")[0])
+        .build()
+        .unwrap();
+    assert_eq!(hand.to_compactstr(), "067m34p33s[^888s][>555+0p]");
+    assert!(HandBuilder::new()
+        .tiles(&tiles("067m34p33s"))
+        .ankan(&tiles("8888s"))
+        .called_from(CalledFrom::Toimen)
+        .build()
+        .is_err());
+    assert_eq!(
+        HandBuilder::new()
+            .tiles(&tiles("067m34p33s"))
+            .called_from(CalledFrom::Toimen)
+            .build(),
+        Err(HandError::MissingMeld)
+    );
+}
+
+#[test]
+fn test_tile_count_array() {
+    for (i, t) in TileId::ALL.iter().enumerate() {
+        assert_eq!(t.index(), i);
+        assert_eq!(TileId::from_index(i), *t);
+    }
+
+    let tiles = TileId::from_tilestr("🀇🀇🀈🀙🀐🀐🀐🀀🀄").unwrap();
+    let counts = TileCountArray::from_tiles(&tiles);
+    assert_eq!(counts.sum(), 9);
+    assert_eq!(counts[TileId::Id1man], 2);
+    assert_eq!(counts[TileId::Id1sou], 3);
+    assert_eq!(counts[TileId::IdChun], 1);
+    assert_eq!(counts.suhai(TileType::MANZU), [2, 1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(counts.jihai(), [1, 0, 0, 0, 0, 0, 1]);
+
+    // Counterとの相互変換
+    let counter = calculate_tile_counts(&tiles);
+    assert_eq!(TileCountArray::from(&counter), counts);
+    assert_eq!(TileCount::from(&counts), counter);
+    assert_eq!(TileCountArray::from(tiles), counts);
+}
@@ -0,0 +1,110 @@
+use mjtools::types::*;
+use mjtools::wall::*;
+
+#[test]
+fn test_wall_shuffle() {
+    let config = WallConfig::default();
+    let wall = Wall::new(&config, 42).unwrap();
+
+    // 同じシードなら同じ並び
+    assert_eq!(wall, Wall::new(&config, 42).unwrap());
+    assert_ne!(wall.tiles(), Wall::new(&config, 43).unwrap().tiles());
+
+    // 赤ドラの枚数
+    assert_eq!(wall.tiles().iter().filter(|t| t.aka).count(), 3);
+    let no_aka = Wall::new(
+        &WallConfig {
+            aka_man: 0,
+            aka_pin: 2,
+            aka_sou: 0,
+        },
+        42,
+    )
+    .unwrap();
+    assert_eq!(no_aka.tiles().iter().filter(|t| t.aka).count(), 2);
+    assert!(Wall::new(
+        &WallConfig {
+            aka_man: 5,
+            ..Default::default()
+        },
+        42
+    )
+    .is_err());
+}
+
+#[test]
+fn test_wall_from_tiles() {
+    let tiles = WallConfig::default().all_tiles().unwrap();
+    assert!(Wall::from_tiles(tiles.clone()).is_ok());
+    assert!(Wall::from_tiles(tiles[1..].to_vec()).is_err());
+
+    // 同じ牌が5枚
+    let mut invalid = tiles.clone();
+    invalid[4] = invalid[0];
+    assert!(Wall::from_tiles(invalid).is_err());
+
+    // 赤ドラにならない牌
+    let mut invalid = tiles;
+    invalid[0].aka = true;
+    assert!(Wall::from_tiles(invalid).is_err());
+}
+
+#[test]
+fn test_wall_draw() {
+    let tiles = WallConfig::default().all_tiles().unwrap();
+    let mut wall = Wall::from_tiles(tiles.clone()).unwrap();
+
+    // 配牌
+    let hands = wall.deal().unwrap();
+    assert!(hands.iter().all(|h| h.len() == 13));
+    assert_eq!(hands[0][..4], tiles[0..4]);
+    assert_eq!(hands[1][..4], tiles[4..8]);
+    assert_eq!(hands[0][12], tiles[48]);
+    assert_eq!(hands[3][12], tiles[51]);
+    assert_eq!(wall.num_remaining(), 70);
+    assert!(wall.deal().is_err());
+
+    // 自摸と嶺上牌
+    assert_eq!(wall.draw(), Some(tiles[52]));
+    assert_eq!(wall.draw_rinshan(), Some(tiles[122]));
+    assert_eq!(wall.num_remaining(), 68);
+    while wall.num_remaining() > 1 {
+        assert!(wall.draw().is_some());
+    }
+    assert!(!wall.is_haitei());
+    // 嶺上牌の分だけ海底牌が繰り上がる
+    assert_eq!(wall.draw(), Some(tiles[120]));
+    assert!(wall.is_haitei());
+    assert_eq!(wall.draw(), None);
+    assert_eq!(wall.draw_rinshan(), None);
+}
+
+#[test]
+fn test_wall_dora() {
+    let tiles = WallConfig::default().all_tiles().unwrap();
+    let mut wall = Wall::from_tiles(tiles.clone()).unwrap();
+
+    assert_eq!(
+        wall.dora(true),
+        Dora {
+            omote: vec![tiles[126]],
+            ura: vec![tiles[127]],
+        }
+    );
+    assert!(wall.dora(false).ura.is_empty());
+
+    // 槓ドラ
+    for i in 1..NUM_MAX_DORA {
+        assert_eq!(wall.flip_dora(), Some(tiles[126 + 2 * i]));
+    }
+    assert_eq!(wall.flip_dora(), None);
+    assert_eq!(wall.num_dora(), 5);
+    assert_eq!(wall.dora(true).omote.len(), 5);
+    assert_eq!(wall.dora(true).ura[4], tiles[135]);
+
+    // 嶺上牌は4枚まで
+    for i in 0..NUM_RINSHAN_TILES {
+        assert_eq!(wall.draw_rinshan(), Some(tiles[122 + i]));
+    }
+    assert_eq!(wall.draw_rinshan(), None);
+}